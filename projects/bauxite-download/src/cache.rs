@@ -0,0 +1,88 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use tracing::debug;
+
+/// The validators of a cached document, stored next to it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// When the document was last fetched or revalidated, in seconds since the epoch
+    pub fetched_at: u64,
+}
+
+impl CacheEntry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+/// An on-disk cache of the metadata documents (version lists, version JSONs...).
+#[derive(Debug, Clone)]
+pub(crate) struct MetadataCache {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+impl MetadataCache {
+    pub fn new(dir: PathBuf, max_age: Duration) -> Self {
+        MetadataCache { dir, max_age }
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = hex::encode(Sha1::digest(url.as_bytes()));
+        (
+            self.dir.join(format!("{}.json", key)),
+            self.dir.join(format!("{}.meta.json", key)),
+        )
+    }
+
+    /// The cached document of the URL, and its validators.
+    pub fn load(&self, url: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let (body_path, entry_path) = self.paths(url);
+
+        let entry: CacheEntry = serde_json::from_slice(&std::fs::read(entry_path).ok()?).ok()?;
+        let body = std::fs::read(body_path).ok()?;
+
+        Some((entry, body))
+    }
+
+    /// Check if a cached document can be used without revalidating it.
+    pub fn is_fresh(&self, entry: &CacheEntry) -> bool {
+        entry.age() < self.max_age
+    }
+
+    /// Store a document and its validators.
+    ///
+    /// The cache is best-effort: the errors are only logged.
+    pub fn store(&self, mut entry: CacheEntry, body: Option<&[u8]>) {
+        entry.fetched_at = now();
+        let (body_path, entry_path) = self.paths(&entry.url);
+
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| match body {
+                Some(body) => std::fs::write(body_path, body),
+                None => Ok(()),
+            })
+            .and_then(|_| {
+                std::fs::write(entry_path, serde_json::to_vec(&entry).unwrap_or_default())
+            });
+
+        if let Err(e) = result {
+            debug!("Failed to cache {}: {:?}", entry.url, e);
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
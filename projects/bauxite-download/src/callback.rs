@@ -0,0 +1,22 @@
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum FileStep {
+    Checking,
+    Downloading { current: u64, total: u64 },
+    Done,
+}
+
+/// Notified of the progress of each downloaded file.
+pub trait FileCallback: Sync + Send {
+    fn on_file_step(&self, path: &Path, step: FileStep);
+}
+
+impl<F> FileCallback for F
+where
+    F: Fn(&Path, FileStep) + Sync + Send,
+{
+    fn on_file_step(&self, path: &Path, step: FileStep) {
+        self(path, step)
+    }
+}
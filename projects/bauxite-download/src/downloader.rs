@@ -0,0 +1,738 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use futures_util::StreamExt;
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE},
+    StatusCode,
+};
+use serde::de::DeserializeOwned;
+use tokio::{
+    fs::OpenOptions,
+    io::AsyncWriteExt,
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+use tracing::{debug, warn};
+
+use crate::{
+    cache::{CacheEntry, MetadataCache},
+    check_file_hash, DownloadError, Endpoints, FetchError, FileCallback, FileStep,
+};
+
+/// A file to download and its expected size and SHA1.
+#[derive(Clone, Debug)]
+pub struct DownloadInfo {
+    pub path: PathBuf,
+    pub url: String,
+    /// The size of the file, 0 if unknown
+    pub size: u64,
+    /// The SHA1 of the file, if known.
+    ///
+    /// Without it, an existing file of the right size (or of any size, if it is unknown too) is
+    /// kept as is.
+    pub sha1: Option<String>,
+}
+
+static SHARED: OnceLock<Downloader> = OnceLock::new();
+
+/// A builder for creating a [`Downloader`].
+#[derive(Debug, Clone)]
+pub struct DownloaderBuilder {
+    retries: u32,
+    retry_delay: Duration,
+    max_concurrent_downloads: usize,
+    max_connections_per_host: usize,
+    keep_alive: Duration,
+    endpoints: Endpoints,
+    metadata_cache: Option<PathBuf>,
+    metadata_max_age: Duration,
+}
+
+impl Default for DownloaderBuilder {
+    fn default() -> Self {
+        DownloaderBuilder {
+            retries: 5,
+            retry_delay: Duration::from_secs(5),
+            max_concurrent_downloads: 32,
+            max_connections_per_host: 8,
+            keep_alive: Duration::from_secs(90),
+            endpoints: Endpoints::default(),
+            metadata_cache: None,
+            metadata_max_age: Duration::ZERO,
+        }
+    }
+}
+
+impl DownloaderBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of attempts for each file (defaults to 5).
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries.max(1);
+        self
+    }
+
+    /// Set the delay between two attempts (defaults to 5 seconds).
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Set the number of files downloaded at the same time (defaults to 32).
+    pub fn with_max_concurrent_downloads(mut self, max: usize) -> Self {
+        self.max_concurrent_downloads = max.max(1);
+        self
+    }
+
+    /// Set the number of connections opened to the same host (defaults to 8).
+    pub fn with_max_connections_per_host(mut self, max: usize) -> Self {
+        self.max_connections_per_host = max.max(1);
+        self
+    }
+
+    /// Set how long the idle connections are kept alive (defaults to 90 seconds).
+    pub fn with_keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Set the endpoints and the rewrite rules applied to the URLs (defaults to the official
+    /// Mojang endpoints).
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Cache the JSON documents in the given folder.
+    ///
+    /// The cached documents are revalidated with conditional requests, and used as is when
+    /// the server can't be reached.
+    pub fn with_metadata_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.metadata_cache = Some(dir.into());
+        self
+    }
+
+    /// Set how long a cached document is used without being revalidated (defaults to 0, the
+    /// documents are always revalidated).
+    pub fn with_metadata_max_age(mut self, max_age: Duration) -> Self {
+        self.metadata_max_age = max_age;
+        self
+    }
+
+    pub fn build(self) -> Downloader {
+        let client = reqwest::Client::builder()
+            .pool_idle_timeout(self.keep_alive)
+            .pool_max_idle_per_host(self.max_connections_per_host)
+            .tcp_keepalive(self.keep_alive)
+            .build()
+            .expect("Failed to create the HTTP client");
+
+        Downloader {
+            client,
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            queue: Arc::new(Semaphore::new(self.max_concurrent_downloads)),
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+            max_connections_per_host: self.max_connections_per_host,
+            endpoints: Arc::new(self.endpoints),
+            cache: self
+                .metadata_cache
+                .map(|dir| MetadataCache::new(dir, self.metadata_max_age)),
+        }
+    }
+}
+
+/// Downloads files with a single pooled HTTP client, checking their hash and retrying on
+/// failure.
+///
+/// The clones of a downloader share the same client and the same limits.
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    client: reqwest::Client,
+    retries: u32,
+    retry_delay: Duration,
+    queue: Arc<Semaphore>,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    max_connections_per_host: usize,
+    endpoints: Arc<Endpoints>,
+    cache: Option<MetadataCache>,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        DownloaderBuilder::default().build()
+    }
+}
+
+impl Downloader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The downloader shared by the crates of the workspace.
+    pub fn shared() -> &'static Downloader {
+        SHARED.get_or_init(Downloader::default)
+    }
+
+    /// Replace the default configuration of the shared downloader.
+    ///
+    /// It must be called before the first use of [`Downloader::shared`], otherwise the given
+    /// downloader is returned as an error.
+    pub fn set_shared(downloader: Downloader) -> Result<(), Downloader> {
+        SHARED.set(downloader)
+    }
+
+    /// The HTTP client used for the downloads.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// The endpoints used by the downloader.
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// Fetch and parse a JSON document, after applying the rewrite rules to its URL.
+    ///
+    /// With a metadata cache, the cached document is used when it is still fresh, when the
+    /// server reports it is not modified, or when the server can't be reached.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, FetchError> {
+        let body = self.get_bytes(url).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch a document, after applying the rewrite rules to its URL.
+    ///
+    /// The metadata cache is used like for [`Downloader::get_json`].
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, FetchError> {
+        let Some(cache) = &self.cache else {
+            let (_, body) = self.fetch(url, None).await?;
+            return Ok(body);
+        };
+
+        let cached = cache.load(url);
+        let body = match cached {
+            Some((entry, body)) if cache.is_fresh(&entry) => {
+                debug!("Using the cached document for {}", url);
+                body
+            }
+            Some((entry, body)) => match self.fetch(url, Some(&entry)).await {
+                Ok((Some(new_entry), new_body)) => {
+                    cache.store(new_entry, Some(&new_body));
+                    new_body
+                }
+                Ok((None, _)) => {
+                    debug!("The cached document for {} is not modified", url);
+                    cache.store(entry, None);
+                    body
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to fetch {}, using the cached document: {:?}",
+                        url, e
+                    );
+                    body
+                }
+            },
+            None => {
+                let (entry, body) = self.fetch(url, None).await?;
+                if let Some(entry) = entry {
+                    cache.store(entry, Some(&body));
+                }
+                body
+            }
+        };
+
+        Ok(body)
+    }
+
+    /// Fetch a document, with a conditional request if the validators of a cached copy are
+    /// given.
+    ///
+    /// Returns the validators of the new document, or `None` if the cached one is not
+    /// modified.
+    async fn fetch(
+        &self,
+        url: &str,
+        cached: Option<&CacheEntry>,
+    ) -> Result<(Option<CacheEntry>, Vec<u8>), reqwest::Error> {
+        let rewritten = self.endpoints.rewrite(url);
+        debug!("Fetching {}", rewritten);
+
+        let _connection = self.host_permit(&rewritten).await;
+        let mut request = self.client.get(&rewritten);
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await?;
+        if cached.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            return Ok((None, Vec::new()));
+        }
+        let response = response.error_for_status()?;
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            ..Default::default()
+        };
+
+        Ok((Some(entry), response.bytes().await?.to_vec()))
+    }
+
+    /// Download a file, unless it already exists with the right hash.
+    ///
+    /// The rewrite rules of the endpoints are applied to the URL of the file.
+    pub async fn download(
+        &self,
+        download_info: DownloadInfo,
+        callback: &dyn FileCallback,
+    ) -> Result<PathBuf, DownloadError> {
+        for attempt in 1..=self.retries {
+            // Wait for a free slot in the download queue
+            let permit = self.queue.acquire().await.expect("Download queue closed");
+            let result = self.download_item(&download_info, callback).await;
+            drop(permit);
+
+            match result {
+                Ok(path) => return Ok(path),
+                Err(e) => {
+                    debug!(
+                        "Error downloading file (attempt {}/{}): ({:?}) {:?}",
+                        attempt, self.retries, download_info.path, e
+                    );
+
+                    if attempt < self.retries {
+                        tokio::time::sleep(self.retry_delay).await;
+                    }
+                }
+            }
+        }
+
+        Err(DownloadError::TooManyRetries(self.retries))
+    }
+
+    async fn download_item(
+        &self,
+        download_info: &DownloadInfo,
+        callback: &dyn FileCallback,
+    ) -> Result<PathBuf, DownloadError> {
+        let path = &download_info.path;
+        debug!("Checking if the file exists: {:?}", path);
+
+        if path.exists() {
+            callback.on_file_step(path, FileStep::Checking);
+
+            match check_file(path, download_info) {
+                Ok(_) => {
+                    callback.on_file_step(path, FileStep::Done);
+                    return Ok(path.clone());
+                }
+                Err(_) => {
+                    debug!("File hash does not match, removing file: {:?}", path);
+                    tokio::fs::remove_file(path).await?;
+                }
+            }
+        }
+
+        // Check if the parent directory exists
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                debug!("Parent directory does not exist, creating it: {:?}", parent);
+                tokio::fs::create_dir_all(parent).await?;
+            }
+        }
+
+        // The file is downloaded next to its final path, and only moved once complete
+        let part_path = part_path(path);
+        let offset = match tokio::fs::metadata(&part_path).await {
+            // Without a size nor a hash, a leftover part cannot be known to be complete
+            Ok(_) if download_info.size == 0 && download_info.sha1.is_none() => 0,
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+
+        if offset == 0 || offset < download_info.size {
+            self.download_part(download_info, &part_path, offset, callback)
+                .await?;
+        }
+
+        debug!("File downloaded: {:?}", path);
+
+        callback.on_file_step(path, FileStep::Checking);
+        if let Err(e) = check_file(&part_path, download_info) {
+            // Delete the file, so it is downloaded again by the next attempt
+            tokio::fs::remove_file(&part_path).await?;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&part_path, path).await?;
+        callback.on_file_step(path, FileStep::Done);
+
+        Ok(path.clone())
+    }
+
+    /// Download a file into its `.part` file, resuming after the first `offset` bytes if the
+    /// server supports range requests.
+    async fn download_part(
+        &self,
+        download_info: &DownloadInfo,
+        part_path: &Path,
+        offset: u64,
+        callback: &dyn FileCallback,
+    ) -> Result<(), DownloadError> {
+        let path = &download_info.path;
+        let url = self.endpoints.rewrite(&download_info.url);
+        debug!(
+            "Downloading file: {:?} from {} (from byte {})",
+            path, url, offset
+        );
+
+        let _connection = self.host_permit(&url).await;
+        let mut request = self.client.get(&url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+        let response = request.send().await?.error_for_status()?;
+
+        // The server may ignore the range and send the whole file
+        let (mut file, mut downloaded) = if response.status() == StatusCode::PARTIAL_CONTENT {
+            let file = OpenOptions::new().append(true).open(part_path).await?;
+            (file, offset)
+        } else {
+            (tokio::fs::File::create(part_path).await?, 0)
+        };
+
+        let mut content = response.bytes_stream();
+
+        while let Some(item) = content.next().await {
+            let chunk = item?;
+            downloaded += chunk.len() as u64;
+
+            file.write_all(&chunk).await?;
+
+            callback.on_file_step(
+                path,
+                FileStep::Downloading {
+                    current: downloaded,
+                    total: download_info.size,
+                },
+            );
+        }
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    /// Wait for a free connection to the host of the URL.
+    async fn host_permit(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let host = reqwest::Url::parse(url).ok()?.host_str()?.to_string();
+
+        let semaphore = self
+            .hosts
+            .lock()
+            .unwrap()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_connections_per_host)))
+            .clone();
+
+        semaphore.acquire_owned().await.ok()
+    }
+}
+
+/// Check a file against the hash of the download, or its size when the hash is unknown.
+fn check_file(path: &Path, download_info: &DownloadInfo) -> Result<(), DownloadError> {
+    match &download_info.sha1 {
+        Some(sha1) => check_file_hash(path, sha1),
+        None if download_info.size == 0 => Ok(()),
+        None if std::fs::metadata(path)?.len() == download_info.size => Ok(()),
+        None => Err(DownloadError::InvalidSize),
+    }
+}
+
+/// The temporary file a download is written to.
+fn part_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // sha1("hello")
+    const HELLO_SHA1: &str = "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d";
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join("bauxite-download-test")
+            .join(name);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(part_path(&path));
+        path
+    }
+
+    #[tokio::test]
+    async fn download_and_skip_existing() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .with_body("hello")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let info = DownloadInfo {
+            path: temp_path("hello.txt"),
+            url: format!("{}/hello", server.url()),
+            size: 5,
+            sha1: Some(HELLO_SHA1.to_string()),
+        };
+        let steps = Mutex::new(Vec::new());
+        let callback = |_: &Path, step: FileStep| steps.lock().unwrap().push(step);
+
+        let downloader = Downloader::new();
+        let path = downloader.download(info.clone(), &callback).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+
+        // The file is up to date, it is not downloaded again
+        downloader.download(info, &callback).await.unwrap();
+        mock.assert_async().await;
+
+        assert!(matches!(steps.lock().unwrap().last(), Some(FileStep::Done)));
+    }
+
+    #[tokio::test]
+    async fn resume_partial_download() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .match_header("range", "bytes=3-")
+            .with_status(206)
+            .with_body("lo")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let info = DownloadInfo {
+            path: temp_path("resumed.txt"),
+            url: format!("{}/hello", server.url()),
+            size: 5,
+            sha1: Some(HELLO_SHA1.to_string()),
+        };
+        std::fs::create_dir_all(info.path.parent().unwrap()).unwrap();
+        std::fs::write(part_path(&info.path), b"hel").unwrap();
+
+        let path = Downloader::new()
+            .download(info.clone(), &|_: &Path, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+        assert!(!part_path(&info.path).exists());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn restart_when_range_is_ignored() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .with_body("hello")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let info = DownloadInfo {
+            path: temp_path("restarted.txt"),
+            url: format!("{}/hello", server.url()),
+            size: 5,
+            sha1: Some(HELLO_SHA1.to_string()),
+        };
+        std::fs::create_dir_all(info.path.parent().unwrap()).unwrap();
+        std::fs::write(part_path(&info.path), b"hel").unwrap();
+
+        let path = Downloader::new()
+            .download(info, &|_: &Path, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn bounded_concurrency() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(50));
+                w.write_all(b"hello")
+            })
+            .expect(4)
+            .create_async()
+            .await;
+
+        let downloader = DownloaderBuilder::new()
+            .with_max_concurrent_downloads(1)
+            .build();
+
+        let start = std::time::Instant::now();
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in 0..4 {
+            let downloader = downloader.clone();
+            let info = DownloadInfo {
+                path: temp_path(&format!("queued-{}.txt", i)),
+                url: format!("{}/hello", server.url()),
+                size: 5,
+                sha1: Some(HELLO_SHA1.to_string()),
+            };
+            tasks.spawn(async move { downloader.download(info, &|_: &Path, _| {}).await });
+        }
+        while let Some(result) = tasks.join_next().await {
+            result.unwrap().unwrap();
+        }
+
+        // The downloads waited for each other
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn cached_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/versions.json", server.url());
+        let dir = std::env::temp_dir()
+            .join("bauxite-download-test")
+            .join("metadata");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let downloader = DownloaderBuilder::new().with_metadata_cache(&dir).build();
+
+        // First fetch, stored with its ETag
+        let mock = server
+            .mock("GET", "/versions.json")
+            .with_header("etag", "\"v1\"")
+            .with_body("[1]")
+            .create_async()
+            .await;
+        let versions: Vec<u32> = downloader.get_json(&url).await.unwrap();
+        assert_eq!(versions, [1]);
+        mock.remove_async().await;
+
+        // Revalidated with the ETag
+        let mock = server
+            .mock("GET", "/versions.json")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .create_async()
+            .await;
+        let versions: Vec<u32> = downloader.get_json(&url).await.unwrap();
+        assert_eq!(versions, [1]);
+        mock.assert_async().await;
+        mock.remove_async().await;
+
+        // Server unavailable, the cached copy is used
+        let mock = server
+            .mock("GET", "/versions.json")
+            .with_status(503)
+            .create_async()
+            .await;
+        let versions: Vec<u32> = downloader.get_json(&url).await.unwrap();
+        assert_eq!(versions, [1]);
+        mock.assert_async().await;
+
+        // Fresh enough, no request at all
+        let downloader = DownloaderBuilder::new()
+            .with_metadata_cache(&dir)
+            .with_metadata_max_age(Duration::from_secs(3600))
+            .build();
+        let versions: Vec<u32> = downloader.get_json(&url).await.unwrap();
+        assert_eq!(versions, [1]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn invalid_checksum() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .with_body("corrupted")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let info = DownloadInfo {
+            path: temp_path("corrupted.txt"),
+            url: format!("{}/hello", server.url()),
+            size: 5,
+            sha1: Some(HELLO_SHA1.to_string()),
+        };
+
+        let downloader = DownloaderBuilder::new()
+            .with_retries(2)
+            .with_retry_delay(Duration::ZERO)
+            .build();
+        let result = downloader.download(info.clone(), &|_: &Path, _| {}).await;
+
+        assert!(matches!(result, Err(DownloadError::TooManyRetries(2))));
+        assert!(!info.path.exists());
+        assert!(!part_path(&info.path).exists());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn download_without_hash() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/hello")
+            .with_body("hello")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let info = DownloadInfo {
+            path: temp_path("unhashed.txt"),
+            url: format!("{}/hello", server.url()),
+            size: 0,
+            sha1: None,
+        };
+        std::fs::create_dir_all(info.path.parent().unwrap()).unwrap();
+        std::fs::write(part_path(&info.path), b"stale").unwrap();
+
+        // The leftover part is not trusted, the file is downloaded from the start
+        let downloader = Downloader::new();
+        let path = downloader
+            .download(info.clone(), &|_: &Path, _| {})
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+
+        // The existing file is kept
+        downloader.download(info, &|_: &Path, _| {}).await.unwrap();
+        mock.assert_async().await;
+    }
+}
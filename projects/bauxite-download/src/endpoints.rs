@@ -0,0 +1,184 @@
+/// The list of the Minecraft versions.
+const VERSION_MANIFEST: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+/// The base URL of the assets objects.
+const RESOURCES: &str = "https://resources.download.minecraft.net";
+/// The list of the Java runtimes.
+const JRE_MANIFEST: &str = "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+/// The Fabric meta API.
+const FABRIC_META: &str = "https://meta.fabricmc.net/v2";
+/// The Quilt meta API.
+const QUILT_META: &str = "https://meta.quiltmc.org/v3";
+/// The maven repository of Forge.
+const FORGE_MAVEN: &str = "https://maven.minecraftforge.net";
+/// The maven repository of NeoForge.
+const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases";
+/// The Modrinth API.
+const MODRINTH_API: &str = "https://api.modrinth.com/v2";
+/// The CurseForge API.
+const CURSEFORGE_API: &str = "https://api.curseforge.com/v1";
+
+/// The hosts replaced by a mirror, and the path they are served from on the mirror.
+const MIRRORED_HOSTS: [(&str, &str); 6] = [
+    ("https://launchermeta.mojang.com", ""),
+    ("https://launcher.mojang.com", ""),
+    ("https://piston-meta.mojang.com", ""),
+    ("https://piston-data.mojang.com", ""),
+    ("https://libraries.minecraft.net", "/maven"),
+    ("https://resources.download.minecraft.net", "/assets"),
+];
+
+/// Generate the setter and the accessor of an endpoint.
+macro_rules! endpoint {
+    ($field:ident, $setter:ident, $description:literal) => {
+        #[doc = concat!("Set the ", $description, ".")]
+        pub fn $setter(mut self, url: impl Into<String>) -> Self {
+            self.$field = url.into();
+            self
+        }
+
+        #[doc = concat!("The ", $description, ", before the rewrite rules.")]
+        pub fn $field(&self) -> &str {
+            &self.$field
+        }
+    };
+}
+
+/// The URLs of the services the files are downloaded from (Mojang, the mod loaders, Modrinth
+/// and CurseForge), and the rewrite rules applied to every downloaded URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    version_manifest: String,
+    resources: String,
+    jre_manifest: String,
+    fabric_meta: String,
+    quilt_meta: String,
+    forge_maven: String,
+    neoforge_maven: String,
+    modrinth_api: String,
+    curseforge_api: String,
+    rewrites: Vec<(String, String)>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            version_manifest: VERSION_MANIFEST.to_string(),
+            resources: RESOURCES.to_string(),
+            jre_manifest: JRE_MANIFEST.to_string(),
+            fabric_meta: FABRIC_META.to_string(),
+            quilt_meta: QUILT_META.to_string(),
+            forge_maven: FORGE_MAVEN.to_string(),
+            neoforge_maven: NEOFORGE_MAVEN.to_string(),
+            modrinth_api: MODRINTH_API.to_string(),
+            curseforge_api: CURSEFORGE_API.to_string(),
+            rewrites: Vec::new(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// The official endpoints, without any rewrite rule.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    endpoint!(
+        version_manifest,
+        with_version_manifest,
+        "URL of the list of the Minecraft versions"
+    );
+    endpoint!(resources, with_resources, "base URL of the assets objects");
+    endpoint!(
+        jre_manifest,
+        with_jre_manifest,
+        "URL of the list of the Java runtimes"
+    );
+    endpoint!(
+        fabric_meta,
+        with_fabric_meta,
+        "base URL of the Fabric meta API"
+    );
+    endpoint!(
+        quilt_meta,
+        with_quilt_meta,
+        "base URL of the Quilt meta API"
+    );
+    endpoint!(
+        forge_maven,
+        with_forge_maven,
+        "base URL of the Forge maven repository"
+    );
+    endpoint!(
+        neoforge_maven,
+        with_neoforge_maven,
+        "base URL of the NeoForge maven repository"
+    );
+    endpoint!(
+        modrinth_api,
+        with_modrinth_api,
+        "base URL of the Modrinth API"
+    );
+    endpoint!(
+        curseforge_api,
+        with_curseforge_api,
+        "base URL of the CurseForge API"
+    );
+
+    /// Replace the `from` prefix of the URLs by `to`.
+    ///
+    /// The rules are checked in the order they were added, and only the first matching rule
+    /// is applied.
+    pub fn with_rewrite(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.rewrites.push((from.into(), to.into()));
+        self
+    }
+
+    /// Rewrite the Mojang hosts to a mirror using the BMCLAPI layout: the metadata and
+    /// versions at its root, the libraries under `/maven` and the assets under `/assets`.
+    pub fn with_mirror(mut self, base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        for (host, path) in MIRRORED_HOSTS {
+            self = self.with_rewrite(host, format!("{}{}", base, path));
+        }
+        self
+    }
+
+    /// Apply the rewrite rules to a URL.
+    pub fn rewrite(&self, url: &str) -> String {
+        for (from, to) in &self.rewrites {
+            if let Some(rest) = url.strip_prefix(from.as_str()) {
+                return format!("{}{}", to, rest);
+            }
+        }
+
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_to_mirror() {
+        let endpoints = Endpoints::new().with_mirror("https://bmclapi2.bangbang93.com/");
+
+        assert_eq!(
+            endpoints.rewrite(endpoints.version_manifest()),
+            "https://bmclapi2.bangbang93.com/mc/game/version_manifest_v2.json"
+        );
+        assert_eq!(
+            endpoints
+                .rewrite("https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar"),
+            "https://bmclapi2.bangbang93.com/maven/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar"
+        );
+        assert_eq!(
+            endpoints.rewrite(endpoints.resources()),
+            "https://bmclapi2.bangbang93.com/assets"
+        );
+        assert_eq!(
+            endpoints.rewrite("https://maven.fabricmc.net/net/fabricmc/"),
+            "https://maven.fabricmc.net/net/fabricmc/"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    #[error("Failed to download the file")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Failed to write the file")]
+    IoError(#[from] std::io::Error),
+
+    #[error("There was an error while calculating the hash {0}")]
+    HashError(#[from] hex::FromHexError),
+
+    #[error("The checksum of the file is invalid")]
+    InvalidChecksum,
+
+    #[error("The size of the file is invalid")]
+    InvalidSize,
+
+    #[error("Failed to download the file after {0} retries")]
+    TooManyRetries(u32),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FetchError {
+    #[error("Failed to fetch the document")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Failed to parse the document")]
+    ParseError(#[from] serde_json::Error),
+}
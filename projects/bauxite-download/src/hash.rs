@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
+use tracing::debug;
+
+use crate::DownloadError;
+
+/// Compute the SHA1 of a file, as an hexadecimal string.
+pub fn file_sha1(path: impl AsRef<Path>) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha1::new();
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Compute the SHA1 of some bytes, as an hexadecimal string.
+pub fn bytes_sha1(bytes: &[u8]) -> String {
+    hex::encode(Sha1::digest(bytes))
+}
+
+/// Compute the SHA512 of a file, as an hexadecimal string.
+pub fn file_sha512(path: impl AsRef<Path>) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha512::new();
+    std::io::copy(&mut file, &mut hasher)?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Check that the SHA1 of a file matches the expected one.
+pub fn check_file_hash(path: impl AsRef<Path>, sha1: &str) -> Result<(), DownloadError> {
+    let path = path.as_ref();
+    debug!("Checking file hash: {:?}", path);
+
+    // Get the remote hash
+    let remote_sha = hex::decode(sha1)?;
+
+    // Get the file hash
+    let hash = hex::decode(file_sha1(path)?)?;
+
+    if hash == remote_sha {
+        debug!("File hash is correct: {:?}", path);
+        return Ok(());
+    }
+
+    Err(DownloadError::InvalidChecksum)
+}
+
+/// Check that the SHA512 of a file matches the expected one.
+pub fn check_file_sha512(path: impl AsRef<Path>, sha512: &str) -> Result<(), DownloadError> {
+    let path = path.as_ref();
+    debug!("Checking file SHA512: {:?}", path);
+
+    if hex::decode(file_sha512(path)?)? == hex::decode(sha512)? {
+        return Ok(());
+    }
+
+    Err(DownloadError::InvalidChecksum)
+}
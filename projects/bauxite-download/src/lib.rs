@@ -0,0 +1,12 @@
+mod cache;
+mod callback;
+mod downloader;
+mod endpoints;
+mod errors;
+mod hash;
+
+pub use callback::{FileCallback, FileStep};
+pub use downloader::{DownloadInfo, Downloader, DownloaderBuilder};
+pub use endpoints::Endpoints;
+pub use errors::{DownloadError, FetchError};
+pub use hash::{bytes_sha1, check_file_hash, check_file_sha512, file_sha1, file_sha512};
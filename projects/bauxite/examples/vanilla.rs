@@ -13,6 +13,14 @@ async fn main() {
 
     let start = std::time::Instant::now();
 
+    // Cache the version JSONs, so the version can be built offline
+    bauxite::Downloader::set_shared(
+        bauxite::DownloaderBuilder::new()
+            .with_metadata_cache(bauxite::Layout::default().metadata_dir())
+            .build(),
+    )
+    .unwrap();
+
     let vanilla_version = VanillaVersionBuilder::new("1.7.10").build().await.unwrap();
     //let latest_version = VanillaVersionBuilder::latest().build().await.unwrap();
 
@@ -22,19 +30,23 @@ async fn main() {
         .with_output_dir("tmp")
         .build();
 
-    let updater = bauxite::Updater::new(instance);
+    let updater = bauxite::Updater::new(&instance);
     updater.update().await.unwrap();
 
     let duration = start.elapsed();
 
     info!("Time elapsed: {:?}", duration);
 
-    /*let auth_info = bauxite::AuthInfo::from_token("access_token", "username");
+    let auth_info = bauxite::AuthInfo::from_token("access_token", "username");
 
     let launcher = bauxite::LauncherBuilder::new(instance)
         .with_java_path("java")
         .with_max_memory(bauxite::Memory::Gigabytes(2))
         .with_auth_info(auth_info)
         .build();
-    launcher.launch(auth_info).await.unwrap();*/
+
+    let mut child = launcher.launch().await.unwrap();
+    let status = child.wait().await.unwrap();
+
+    info!("Game exited with status: {}", status);
 }
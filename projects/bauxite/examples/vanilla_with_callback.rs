@@ -0,0 +1,77 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use bauxite::{
+    minecraft::vanilla::VanillaVersionBuilder, UpdateCallback, UpdateFileStep, UpdateProgress,
+    UpdateStep,
+};
+use indicatif::{ProgressBar, ProgressStyle};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+struct MyCallback {
+    pb: ProgressBar,
+}
+
+impl MyCallback {
+    pub fn new() -> Self {
+        let pb = ProgressBar::new(100);
+        pb.set_style(
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] {bar:80.cyan/blue} {bytes:>7}/{total_bytes:7} {msg}",
+            )
+            .unwrap()
+            .progress_chars("##-"),
+        );
+        Self { pb }
+    }
+}
+
+impl UpdateCallback for MyCallback {
+    fn on_start(&self) {
+        self.pb.set_message("Starting update");
+        self.pb.enable_steady_tick(Duration::from_millis(100));
+        self.pb.reset();
+    }
+
+    fn on_step(&self, step: UpdateStep) {
+        let txt = match step {
+            UpdateStep::AssetsIndex => "Downloading the assets index",
+            UpdateStep::Libraries => "Downloading the libraries",
+            UpdateStep::Client => "Downloading the client",
+            UpdateStep::Natives => "Downloading the native libraries",
+            UpdateStep::Assets => "Downloading the assets",
+            UpdateStep::Done => "Finished",
+        };
+        self.pb.set_message(txt);
+    }
+
+    fn on_total(&self, total: &UpdateProgress) {
+        self.pb.set_length(total.total_bytes);
+    }
+
+    fn on_file_step(&self, _path: &Path, _step: UpdateFileStep) {}
+
+    fn on_file_downloaded(&self, _path: &Path, progress: &UpdateProgress) {
+        self.pb.set_position(progress.bytes);
+    }
+
+    fn on_finish(&self) {
+        self.pb.finish();
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(fmt::layer().pretty())
+        .with(EnvFilter::from_default_env())
+        .init();
+
+    let vanilla_version = VanillaVersionBuilder::new("1.7.10").build().await.unwrap();
+
+    let instance = bauxite::InstanceBuilder::new(vanilla_version)
+        .with_output_dir("tmp")
+        .build();
+
+    let updater = bauxite::Updater::new(&instance).with_callback(Arc::new(MyCallback::new()));
+    updater.update().await.unwrap();
+}
@@ -1,2 +0,0 @@
-pub const VANILLA_VERSIONS: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-pub const RESOURCES_BASE: &str = "https://resources.download.minecraft.net";
@@ -1,9 +1,13 @@
-use std::path::{Path, PathBuf};
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+};
 
-use crate::minecraft::version::MinecraftVersion;
+use crate::{layout::Layout, minecraft::version::MinecraftVersion};
 
 pub struct InstanceBuilder {
     output_dir: Option<PathBuf>,
+    layout: Option<Layout>,
     mc_version: Box<dyn MinecraftVersion + 'static>,
 }
 
@@ -11,18 +15,28 @@ impl InstanceBuilder {
     pub fn new<V: MinecraftVersion + 'static>(version: V) -> Self {
         InstanceBuilder {
             output_dir: None,
+            layout: None,
             mc_version: Box::new(version),
         }
     }
 
+    /// Set the game directory of the instance (saves, mods, config...).
     pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
         self.output_dir = Some(output_dir.into());
         self
     }
 
+    /// Set the folder where the libraries, assets and versions shared between instances are
+    /// stored (defaults to [`Layout::default`]).
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.layout = Some(Layout::new(cache_dir));
+        self
+    }
+
     pub fn build(self) -> Instance {
         Instance {
             output_dir: self.output_dir.unwrap(),
+            layout: self.layout.unwrap_or_default(),
             mc_version: self.mc_version,
         }
     }
@@ -30,15 +44,33 @@ impl InstanceBuilder {
 
 pub struct Instance {
     output_dir: PathBuf,
+    layout: Layout,
     mc_version: Box<dyn MinecraftVersion>,
 }
 
 impl Instance {
+    /// The game directory of the instance.
     pub fn output_dir(&self) -> &Path {
         &self.output_dir
     }
 
+    /// The folder the mods of the instance are installed in.
+    pub fn mods_dir(&self) -> PathBuf {
+        self.output_dir.join("mods")
+    }
+
+    /// The shared folders used by the instance.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
     pub fn mc_version(&self) -> &dyn MinecraftVersion {
         self.mc_version.as_ref()
     }
+
+    /// The version of the instance, if it is a `V` (such as a Forge version).
+    pub fn version<V: MinecraftVersion>(&self) -> Option<&V> {
+        let version: &dyn Any = self.mc_version.as_ref();
+        version.downcast_ref()
+    }
 }
@@ -0,0 +1,49 @@
+/// The UUID used when no player UUID is provided.
+const DEFAULT_UUID: &str = "00000000-0000-0000-0000-000000000000";
+
+/// The authentication information passed to the game.
+#[derive(Debug, Clone)]
+pub struct AuthInfo {
+    /// The player name
+    pub username: String,
+    /// The player UUID
+    pub uuid: String,
+    /// The access token used to join online servers
+    pub access_token: String,
+    /// The account type (`msa`, `mojang` or `legacy`)
+    pub user_type: String,
+}
+
+impl AuthInfo {
+    /// Create the authentication information from an access token and a player name.
+    pub fn from_token(access_token: &str, username: &str) -> Self {
+        AuthInfo {
+            username: username.to_string(),
+            uuid: DEFAULT_UUID.to_string(),
+            access_token: access_token.to_string(),
+            user_type: "msa".to_string(),
+        }
+    }
+
+    /// Create the authentication information for an offline player.
+    pub fn offline(username: &str) -> Self {
+        AuthInfo {
+            username: username.to_string(),
+            uuid: DEFAULT_UUID.to_string(),
+            access_token: "0".to_string(),
+            user_type: "legacy".to_string(),
+        }
+    }
+
+    /// Set the player UUID.
+    pub fn with_uuid(mut self, uuid: &str) -> Self {
+        self.uuid = uuid.to_string();
+        self
+    }
+
+    /// Set the account type.
+    pub fn with_user_type(mut self, user_type: &str) -> Self {
+        self.user_type = user_type.to_string();
+        self
+    }
+}
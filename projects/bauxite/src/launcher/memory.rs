@@ -0,0 +1,16 @@
+/// An amount of memory given to the JVM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Memory {
+    Megabytes(u32),
+    Gigabytes(u32),
+}
+
+impl Memory {
+    /// Format the memory as a JVM size (e.g. `512M` or `2G`).
+    pub fn to_jvm_size(&self) -> String {
+        match self {
+            Memory::Megabytes(value) => format!("{}M", value),
+            Memory::Gigabytes(value) => format!("{}G", value),
+        }
+    }
+}
@@ -0,0 +1,430 @@
+use std::path::{Path, PathBuf};
+
+use tokio::process::{Child, Command};
+use tracing::debug;
+
+use crate::{
+    instance::Instance,
+    minecraft::{
+        arguments::{self, ArgumentContext},
+        assets::legacy_assets_dir,
+        classpath::{Classpath, CLASSPATH_SEPARATOR},
+        jsons::assets_index::AssetsList,
+        rules::RuleContext,
+    },
+};
+
+mod auth;
+pub use auth::AuthInfo;
+mod memory;
+pub use memory::Memory;
+
+/// The launcher name given to the game.
+const LAUNCHER_NAME: &str = "bauxite";
+/// The launcher version given to the game.
+const LAUNCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A builder for creating a [`Launcher`].
+pub struct LauncherBuilder {
+    instance: Instance,
+    java_path: Option<PathBuf>,
+    min_memory: Option<Memory>,
+    max_memory: Option<Memory>,
+    auth_info: Option<AuthInfo>,
+    resolution: Option<(u32, u32)>,
+    features: Vec<(String, bool)>,
+    jvm_args: Vec<String>,
+    game_args: Vec<String>,
+}
+
+impl LauncherBuilder {
+    /// Create a new builder for the given instance.
+    pub fn new(instance: Instance) -> Self {
+        LauncherBuilder {
+            instance,
+            java_path: None,
+            min_memory: None,
+            max_memory: None,
+            auth_info: None,
+            resolution: None,
+            features: Vec::new(),
+            jvm_args: Vec::new(),
+            game_args: Vec::new(),
+        }
+    }
+
+    /// Set the path to the java executable (defaults to `java`).
+    pub fn with_java_path(mut self, java_path: impl Into<PathBuf>) -> Self {
+        self.java_path = Some(java_path.into());
+        self
+    }
+
+    /// Set the initial memory allocation pool (`-Xms`).
+    pub fn with_min_memory(mut self, memory: Memory) -> Self {
+        self.min_memory = Some(memory);
+        self
+    }
+
+    /// Set the maximum memory allocation pool (`-Xmx`).
+    pub fn with_max_memory(mut self, memory: Memory) -> Self {
+        self.max_memory = Some(memory);
+        self
+    }
+
+    /// Set the authentication information (defaults to an offline player).
+    pub fn with_auth_info(mut self, auth_info: AuthInfo) -> Self {
+        self.auth_info = Some(auth_info);
+        self
+    }
+
+    /// Set the size of the game window.
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = Some((width, height));
+        self
+    }
+
+    /// Enable or disable a feature checked by the game arguments rules (e.g. `is_demo_user`).
+    pub fn with_feature(mut self, feature: &str, enabled: bool) -> Self {
+        self.features.push((feature.to_string(), enabled));
+        self
+    }
+
+    /// Add extra JVM arguments, placed before the main class.
+    pub fn with_jvm_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.jvm_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add extra game arguments, placed after the version arguments.
+    pub fn with_game_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.game_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Build the [`Launcher`].
+    pub fn build(self) -> Launcher {
+        let rules = self.features.iter().fold(
+            RuleContext::current().with_feature("has_custom_resolution", self.resolution.is_some()),
+            |rules, (feature, enabled)| rules.with_feature(feature, *enabled),
+        );
+
+        Launcher {
+            instance: self.instance,
+            java_path: self.java_path.unwrap_or_else(|| PathBuf::from("java")),
+            min_memory: self.min_memory,
+            max_memory: self.max_memory,
+            auth_info: self
+                .auth_info
+                .unwrap_or_else(|| AuthInfo::offline("Player")),
+            resolution: self.resolution,
+            rules,
+            jvm_args: self.jvm_args,
+            game_args: self.game_args,
+        }
+    }
+}
+
+/// Launch an [`Instance`] of the game.
+pub struct Launcher {
+    instance: Instance,
+    java_path: PathBuf,
+    min_memory: Option<Memory>,
+    max_memory: Option<Memory>,
+    auth_info: AuthInfo,
+    resolution: Option<(u32, u32)>,
+    rules: RuleContext,
+    jvm_args: Vec<String>,
+    game_args: Vec<String>,
+}
+
+impl Launcher {
+    pub fn instance(&self) -> &Instance {
+        &self.instance
+    }
+
+    pub fn java_path(&self) -> &Path {
+        &self.java_path
+    }
+
+    /// Assemble the arguments given to the java executable.
+    pub fn arguments(&self) -> Vec<String> {
+        let manifest = self.instance.mc_version().manifest();
+        let context = self.argument_context();
+
+        let mut args = Vec::new();
+
+        if let Some(memory) = &self.min_memory {
+            args.push(format!("-Xms{}", memory.to_jvm_size()));
+        }
+        if let Some(memory) = &self.max_memory {
+            args.push(format!("-Xmx{}", memory.to_jvm_size()));
+        }
+
+        args.extend(arguments::jvm_arguments(manifest, &context, &self.rules));
+        args.extend(self.jvm_args.iter().cloned());
+
+        args.push(manifest.main_class.clone());
+
+        args.extend(arguments::game_arguments(manifest, &context, &self.rules));
+        args.extend(self.game_args.iter().cloned());
+
+        args
+    }
+
+    /// Spawn the game process.
+    ///
+    /// The returned [`Child`] can be awaited or killed by the caller.
+    pub async fn launch(&self) -> Result<Child, LauncherError> {
+        let game_dir = self.instance.output_dir();
+        if !game_dir.exists() {
+            debug!(
+                "Game directory does not exist, creating it at {:?}",
+                game_dir
+            );
+            tokio::fs::create_dir_all(game_dir).await?;
+        }
+
+        let args = self.arguments();
+        debug!("Launching {:?} with arguments: {:?}", self.java_path, args);
+
+        let child = Command::new(&self.java_path)
+            .args(&args)
+            .current_dir(game_dir)
+            .spawn()
+            .map_err(LauncherError::SpawnError)?;
+
+        Ok(child)
+    }
+
+    /// Build the classpath of the game.
+    pub fn classpath(&self) -> Classpath {
+        let manifest = self.instance.mc_version().manifest();
+        let layout = self.instance.layout();
+
+        Classpath::from_manifest(
+            manifest,
+            &self.rules,
+            layout.libraries_dir(),
+            layout.client_jar(manifest.jar()),
+        )
+    }
+
+    /// The values of the `${...}` placeholders found in the version arguments.
+    pub fn argument_context(&self) -> ArgumentContext {
+        let manifest = self.instance.mc_version().manifest();
+        let layout = self.instance.layout();
+        let game_dir = self.instance.output_dir();
+
+        // Legacy assets indexes are used by name from their own folder
+        let index_id = &manifest.asset_index.id;
+        let game_assets = AssetsList::from_file(layout.assets_index(index_id))
+            .ok()
+            .and_then(|index| legacy_assets_dir(&index, index_id, layout, game_dir))
+            .unwrap_or_else(|| layout.assets_dir());
+
+        ArgumentContext {
+            auth_player_name: self.auth_info.username.clone(),
+            auth_uuid: self.auth_info.uuid.clone(),
+            auth_access_token: self.auth_info.access_token.clone(),
+            user_type: self.auth_info.user_type.clone(),
+            version_name: manifest.id.clone(),
+            version_type: manifest.version_type.as_ref().to_string(),
+            game_directory: game_dir.to_path_buf(),
+            assets_root: layout.assets_dir(),
+            game_assets,
+            assets_index_name: manifest.asset_index.id.clone(),
+            natives_directory: layout.natives_dir(&manifest.id),
+            library_directory: layout.libraries_dir(),
+            classpath: self.classpath().to_string(),
+            classpath_separator: CLASSPATH_SEPARATOR.to_string(),
+            launcher_name: LAUNCHER_NAME.to_string(),
+            launcher_version: LAUNCHER_VERSION.to_string(),
+            resolution: self.resolution,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LauncherError {
+    #[error("Failed to prepare the game directory")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to spawn the game process")]
+    SpawnError(#[source] std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{minecraft::jsons::version_manifest::McVersionManifest, InstanceBuilder};
+
+    use super::*;
+
+    struct OfflineVersion {
+        id: String,
+        manifest: McVersionManifest,
+    }
+
+    impl crate::minecraft::version::MinecraftVersion for OfflineVersion {
+        fn id(&self) -> &String {
+            &self.id
+        }
+
+        fn name(&self) -> &String {
+            &self.id
+        }
+
+        fn is_snapshot(&self) -> bool {
+            false
+        }
+
+        fn json_url(&self) -> &String {
+            &self.id
+        }
+
+        fn manifest(&self) -> &McVersionManifest {
+            &self.manifest
+        }
+    }
+
+    fn launcher(builder: impl FnOnce(LauncherBuilder) -> LauncherBuilder) -> Launcher {
+        let manifest = serde_json::from_value(serde_json::json!({
+            "arguments": {
+                "game": [
+                    "--username", "${auth_player_name}",
+                    "--uuid", "${auth_uuid}",
+                    "--accessToken", "${auth_access_token}",
+                    "--userType", "${user_type}",
+                    "--gameDir", "${game_directory}"
+                ],
+                "jvm": ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"]
+            },
+            "assetIndex": { "id": "12", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "12",
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.20.4",
+            "libraries": [
+                {
+                    "name": "org.ow2.asm:asm:9.6",
+                    "downloads": {
+                        "artifact": { "path": "org/ow2/asm/asm/9.6/asm-9.6.jar", "sha1": "", "size": 0, "url": "" }
+                    }
+                },
+                {
+                    "name": "org.ow2.asm:asm:9.3",
+                    "downloads": {
+                        "artifact": { "path": "org/ow2/asm/asm/9.3/asm-9.3.jar", "sha1": "", "size": 0, "url": "" }
+                    }
+                },
+                {
+                    "name": "com.mojang:brigadier:1.2.9",
+                    "downloads": {
+                        "artifact": { "path": "com/mojang/brigadier/1.2.9/brigadier-1.2.9.jar", "sha1": "", "size": 0, "url": "" }
+                    }
+                }
+            ],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 21,
+            "releaseTime": "2023-12-07T12:56:20+00:00",
+            "time": "2023-12-07T12:56:20+00:00",
+            "type": "release"
+        }))
+        .unwrap();
+
+        let instance = InstanceBuilder::new(OfflineVersion {
+            id: "1.20.4".to_string(),
+            manifest,
+        })
+        .with_output_dir("/game")
+        .with_cache_dir("/cache")
+        .build();
+
+        builder(LauncherBuilder::new(instance)).build()
+    }
+
+    #[test]
+    fn classpath_order() {
+        let launcher = launcher(|builder| builder);
+        let layout = launcher.instance().layout();
+
+        assert_eq!(
+            launcher.classpath().entries(),
+            [
+                layout
+                    .libraries_dir()
+                    .join("org/ow2/asm/asm/9.6/asm-9.6.jar"),
+                layout
+                    .libraries_dir()
+                    .join("com/mojang/brigadier/1.2.9/brigadier-1.2.9.jar"),
+                layout.client_jar("1.20.4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn argument_context_values() {
+        let launcher = launcher(|builder| {
+            builder
+                .with_auth_info(AuthInfo::from_token("token", "Steve").with_uuid("1234"))
+                .with_resolution(854, 480)
+        });
+        let context = launcher.argument_context();
+
+        assert_eq!(context.auth_player_name, "Steve");
+        assert_eq!(context.auth_uuid, "1234");
+        assert_eq!(context.auth_access_token, "token");
+        assert_eq!(context.user_type, "msa");
+        assert_eq!(context.version_name, "1.20.4");
+        assert_eq!(context.game_directory, PathBuf::from("/game"));
+        assert_eq!(
+            context.game_assets,
+            launcher.instance().layout().assets_dir()
+        );
+        assert_eq!(context.classpath, launcher.classpath().to_string());
+        assert_eq!(context.resolution, Some((854, 480)));
+    }
+
+    #[test]
+    fn arguments_order() {
+        let launcher = launcher(|builder| {
+            builder
+                .with_min_memory(Memory::Megabytes(512))
+                .with_max_memory(Memory::Gigabytes(2))
+                .with_jvm_args(["-XX:+UseG1GC"])
+                .with_game_args(["--demo"])
+        });
+        let natives = launcher.instance().layout().natives_dir("1.20.4");
+
+        assert_eq!(
+            launcher.arguments(),
+            [
+                "-Xms512M".to_string(),
+                "-Xmx2G".to_string(),
+                format!("-Djava.library.path={}", natives.to_string_lossy()),
+                "-cp".to_string(),
+                launcher.classpath().to_string(),
+                "-XX:+UseG1GC".to_string(),
+                "net.minecraft.client.main.Main".to_string(),
+                "--username".to_string(),
+                "Player".to_string(),
+                "--uuid".to_string(),
+                "00000000-0000-0000-0000-000000000000".to_string(),
+                "--accessToken".to_string(),
+                "0".to_string(),
+                "--userType".to_string(),
+                "legacy".to_string(),
+                "--gameDir".to_string(),
+                PathBuf::from("/game").to_string_lossy().to_string(),
+                "--demo".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+/// The folders where the game files shared between instances are stored: libraries, assets
+/// and versions.
+///
+/// Each [`Instance`](crate::Instance) keeps its own game directory (saves, mods, config...),
+/// so several instances can share the same layout without clobbering each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    root: PathBuf,
+}
+
+impl Layout {
+    /// Create a layout stored in the given folder.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Layout { root: root.into() }
+    }
+
+    /// The root of the shared folders.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The folder containing the libraries, in the maven layout.
+    pub fn libraries_dir(&self) -> PathBuf {
+        self.root.join("libraries")
+    }
+
+    /// The folder containing the assets indexes and objects.
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+
+    /// The assets index with the given id.
+    pub fn assets_index(&self, id: &str) -> PathBuf {
+        self.assets_dir()
+            .join("indexes")
+            .join(format!("{}.json", id))
+    }
+
+    /// The folder where the assets of a virtual assets index are copied by name.
+    pub fn virtual_assets_dir(&self, id: &str) -> PathBuf {
+        self.assets_dir().join("virtual").join(id)
+    }
+
+    /// The folder where the version lists and JSONs are cached, see
+    /// [`DownloaderBuilder::with_metadata_cache`](crate::DownloaderBuilder::with_metadata_cache).
+    pub fn metadata_dir(&self) -> PathBuf {
+        self.root.join("metadata")
+    }
+
+    /// The folder containing the versions.
+    pub fn versions_dir(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    /// The folder of a version, containing its client jar.
+    pub fn version_dir(&self, id: &str) -> PathBuf {
+        self.versions_dir().join(id)
+    }
+
+    /// The JSON of a version.
+    pub fn version_json(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{}.json", id))
+    }
+
+    /// The client jar of a version.
+    pub fn client_jar(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join(format!("{}.jar", id))
+    }
+
+    /// The folder where the native libraries of a version are extracted.
+    pub fn natives_dir(&self, id: &str) -> PathBuf {
+        self.version_dir(id).join("natives")
+    }
+}
+
+impl Default for Layout {
+    /// The default layout, stored in the bauxite data folder, next to (and not inside) the
+    /// official Minecraft folder.
+    fn default() -> Self {
+        Layout::new(data_folder())
+    }
+}
+
+/// Returns the path to the bauxite data folder.
+fn data_folder() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let appdata = std::env::var("APPDATA").expect("APPDATA environment variable not found");
+        PathBuf::from(appdata).join("bauxite")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME").expect("HOME environment variable not found");
+        PathBuf::from(home).join("Library/Application Support/bauxite")
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match std::env::var("XDG_DATA_HOME") {
+            Ok(data_home) if !data_home.is_empty() => PathBuf::from(data_home).join("bauxite"),
+            _ => {
+                let home = std::env::var("HOME").expect("HOME environment variable not found");
+                PathBuf::from(home).join(".local/share/bauxite")
+            }
+        }
+    }
+}
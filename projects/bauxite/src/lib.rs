@@ -2,10 +2,18 @@ pub mod minecraft;
 pub mod modloaders;
 pub mod mods;
 
-mod constants;
 mod instance;
-pub use instance::InstanceBuilder;
+mod layout;
+pub use instance::{Instance, InstanceBuilder};
+pub use layout::Layout;
+mod launcher;
+pub use launcher::{AuthInfo, Launcher, LauncherBuilder, LauncherError, Memory};
 mod updater;
-pub use updater::Updater;
+pub use updater::{
+    callback::{DefaultUpdateCallback, UpdateCallback, UpdateFileStep, UpdateProgress, UpdateStep},
+    Updater,
+};
 
 mod utils;
+
+pub use bauxite_download::{DownloadError, Downloader, DownloaderBuilder, Endpoints, FetchError};
@@ -0,0 +1,279 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+use tracing::trace;
+
+use super::{
+    jsons::version_manifest::{Argument, McVersionManifest, OsRule},
+    rules::{Rule, RuleContext},
+};
+
+/// The JVM arguments used by versions older than 1.13 (17w43a), which only have the
+/// `minecraftArguments` field. They are the same as the ones used by the official launcher.
+const LEGACY_JVM_ARGUMENTS: &str = r#"[
+    {
+        "rules": [{ "action": "allow", "os": { "name": "osx" } }],
+        "value": ["-XstartOnFirstThread"]
+    },
+    {
+        "rules": [{ "action": "allow", "os": { "name": "windows" } }],
+        "value": "-XX:HeapDumpPath=MojangTricksIntelFriendsWithHeapDumps.exe_javaw.exe_minecraft.exe.heapdump"
+    },
+    {
+        "rules": [{ "action": "allow", "os": { "arch": "x86" } }],
+        "value": "-Xss1M"
+    },
+    "-Djava.library.path=${natives_directory}",
+    "-Dminecraft.launcher.brand=${launcher_name}",
+    "-Dminecraft.launcher.version=${launcher_version}",
+    "-cp",
+    "${classpath}"
+]"#;
+
+/// The values substituted in the `${...}` placeholders of the version arguments.
+#[derive(Debug, Clone, Default)]
+pub struct ArgumentContext {
+    pub auth_player_name: String,
+    pub auth_uuid: String,
+    pub auth_access_token: String,
+    pub auth_xuid: String,
+    pub clientid: String,
+    pub user_type: String,
+    /// The legacy twitch user properties, `{}` when empty.
+    pub user_properties: String,
+    pub version_name: String,
+    pub version_type: String,
+    pub game_directory: PathBuf,
+    pub assets_root: PathBuf,
+    /// The assets directory of legacy versions (`${game_assets}`).
+    pub game_assets: PathBuf,
+    pub assets_index_name: String,
+    pub natives_directory: PathBuf,
+    pub library_directory: PathBuf,
+    pub classpath: String,
+    pub classpath_separator: String,
+    pub launcher_name: String,
+    pub launcher_version: String,
+    /// The custom window size
+    pub resolution: Option<(u32, u32)>,
+}
+
+impl ArgumentContext {
+    /// Get the value of a placeholder.
+    pub fn value(&self, key: &str) -> Option<String> {
+        let path = |p: &PathBuf| p.to_string_lossy().to_string();
+
+        let value = match key {
+            "auth_player_name" => self.auth_player_name.clone(),
+            "auth_uuid" => self.auth_uuid.clone(),
+            "auth_access_token" | "auth_session" => self.auth_access_token.clone(),
+            "auth_xuid" => self.auth_xuid.clone(),
+            "clientid" => self.clientid.clone(),
+            "user_type" => self.user_type.clone(),
+            "user_properties" if self.user_properties.is_empty() => "{}".to_string(),
+            "user_properties" => self.user_properties.clone(),
+            "version_name" => self.version_name.clone(),
+            "version_type" => self.version_type.clone(),
+            "game_directory" => path(&self.game_directory),
+            "assets_root" => path(&self.assets_root),
+            "game_assets" => path(&self.game_assets),
+            "assets_index_name" => self.assets_index_name.clone(),
+            "natives_directory" => path(&self.natives_directory),
+            "library_directory" => path(&self.library_directory),
+            "classpath" => self.classpath.clone(),
+            "classpath_separator" => self.classpath_separator.clone(),
+            "launcher_name" => self.launcher_name.clone(),
+            "launcher_version" => self.launcher_version.clone(),
+            "resolution_width" => self.resolution?.0.to_string(),
+            "resolution_height" => self.resolution?.1.to_string(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Replace the `${...}` placeholders of an argument with their values.
+    /// Unknown placeholders are kept as is.
+    pub fn substitute(&self, argument: &str) -> String {
+        let mut result = String::with_capacity(argument.len());
+        let mut rest = argument;
+
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+
+            let Some(end) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+
+            let key = &rest[start + 2..start + end];
+            match self.value(key) {
+                Some(value) => result.push_str(&value),
+                None => {
+                    trace!("Unknown argument placeholder: {}", key);
+                    result.push_str(&rest[start..=start + end]);
+                }
+            }
+            rest = &rest[start + end + 1..];
+        }
+        result.push_str(rest);
+
+        result
+    }
+}
+
+/// Resolve the JVM arguments of a version.
+///
+/// Versions without the `arguments` field fall back to the default JVM arguments of the
+/// official launcher.
+pub fn jvm_arguments(
+    manifest: &McVersionManifest,
+    context: &ArgumentContext,
+    rules: &RuleContext,
+) -> Vec<String> {
+    let arguments = match &manifest.arguments {
+        Some(arguments) => &arguments.jvm,
+        None => legacy_jvm_arguments(),
+    };
+
+    resolve(arguments, context, rules)
+}
+
+/// Resolve the game arguments of a version, from the `arguments.game` field or the legacy
+/// `minecraftArguments` field.
+pub fn game_arguments(
+    manifest: &McVersionManifest,
+    context: &ArgumentContext,
+    rules: &RuleContext,
+) -> Vec<String> {
+    match (&manifest.arguments, &manifest.minecraft_arguments) {
+        (Some(arguments), _) => resolve(&arguments.game, context, rules),
+        (None, Some(arguments)) => arguments
+            .split_whitespace()
+            .map(|argument| context.substitute(argument))
+            .collect(),
+        (None, None) => Vec::new(),
+    }
+}
+
+fn legacy_jvm_arguments() -> &'static Vec<Argument<OsRule>> {
+    static ARGUMENTS: OnceLock<Vec<Argument<OsRule>>> = OnceLock::new();
+    ARGUMENTS.get_or_init(|| {
+        serde_json::from_str(LEGACY_JVM_ARGUMENTS).expect("Invalid legacy JVM arguments")
+    })
+}
+
+fn resolve<R: Rule>(
+    arguments: &[Argument<R>],
+    context: &ArgumentContext,
+    rules: &RuleContext,
+) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for argument in arguments {
+        match argument {
+            Argument::Simple(value) => result.push(context.substitute(value)),
+            Argument::Conditional {
+                rules: conditions,
+                value,
+            } => {
+                if rules.allows(conditions) {
+                    result.extend(value.iter().map(|v| context.substitute(v)));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(arguments: &str) -> McVersionManifest {
+        serde_json::from_str(&format!(
+            r#"{{
+                {arguments}
+                "assetIndex": {{ "id": "1.7.10", "sha1": "", "size": 0, "totalSize": 0, "url": "" }},
+                "assets": "1.7.10",
+                "downloads": {{ "client": {{ "sha1": "", "size": 0, "url": "" }} }},
+                "id": "1.7.10",
+                "libraries": [],
+                "mainClass": "net.minecraft.client.main.Main",
+                "minimumLauncherVersion": 13,
+                "releaseTime": "2014-05-14T17:29:23+00:00",
+                "time": "2014-05-14T17:29:23+00:00",
+                "type": "release"
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn substitute_placeholders() {
+        let context = ArgumentContext {
+            auth_player_name: "Steve".to_string(),
+            natives_directory: PathBuf::from("/natives"),
+            ..Default::default()
+        };
+
+        assert_eq!(context.substitute("${auth_player_name}"), "Steve");
+        assert_eq!(
+            context.substitute("-Djava.library.path=${natives_directory}"),
+            "-Djava.library.path=/natives"
+        );
+        assert_eq!(context.substitute("${unknown}"), "${unknown}");
+        assert_eq!(context.substitute("${unclosed"), "${unclosed");
+    }
+
+    #[test]
+    fn legacy_arguments() {
+        let manifest = manifest(
+            r#""minecraftArguments": "--username ${auth_player_name} --userProperties ${user_properties}","#,
+        );
+        let context = ArgumentContext {
+            auth_player_name: "Steve".to_string(),
+            classpath: "client.jar".to_string(),
+            ..Default::default()
+        };
+
+        let rules = RuleContext::current();
+
+        assert_eq!(
+            game_arguments(&manifest, &context, &rules),
+            ["--username", "Steve", "--userProperties", "{}"]
+        );
+
+        let jvm = jvm_arguments(&manifest, &context, &rules);
+        assert!(jvm.ends_with(&["-cp".to_string(), "client.jar".to_string()]));
+    }
+
+    #[test]
+    fn game_arguments_features() {
+        let manifest = manifest(
+            r#""arguments": {
+                "game": [
+                    "--version", "${version_name}",
+                    { "rules": [{ "action": "allow", "features": { "is_demo_user": true } }], "value": "--demo" },
+                    {
+                        "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+                        "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+                    }
+                ],
+                "jvm": []
+            },"#,
+        );
+        let context = ArgumentContext {
+            version_name: "1.20.4".to_string(),
+            resolution: Some((854, 480)),
+            ..Default::default()
+        };
+
+        let rules = RuleContext::current().with_feature("has_custom_resolution", true);
+
+        assert_eq!(
+            game_arguments(&manifest, &context, &rules),
+            ["--version", "1.20.4", "--width", "854", "--height", "480"]
+        );
+    }
+}
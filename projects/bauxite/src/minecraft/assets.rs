@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use bauxite_download::file_sha1;
+use tracing::{debug, trace};
+
+use crate::layout::Layout;
+
+use super::jsons::assets_index::{AssetObject, AssetsList};
+
+/// The path of an asset object in the assets folder: `objects/<first two hex>/<hash>`.
+pub fn object_path(assets_dir: impl AsRef<Path>, object: &AssetObject) -> PathBuf {
+    assets_dir
+        .as_ref()
+        .join("objects")
+        .join(&object.hash[..2])
+        .join(&object.hash)
+}
+
+/// Move the assets downloaded by name into `objects/<name>` by the previous versions of the
+/// updater to their hash-addressed path.
+///
+/// The files are only moved if their hash matches the index, otherwise they are removed. The
+/// folders left empty are removed as well.
+pub fn migrate_objects(index: &AssetsList, assets_dir: impl AsRef<Path>) -> std::io::Result<()> {
+    let objects_dir = assets_dir.as_ref().join("objects");
+
+    for (name, object) in &index.objects {
+        let legacy = objects_dir.join(name);
+        if !legacy.is_file() {
+            continue;
+        }
+
+        let target = object_path(&assets_dir, object);
+
+        if !target.exists() && file_sha1(&legacy)? == object.hash {
+            debug!("Moving asset {} to {:?}", name, target);
+
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(&legacy, &target)?;
+        } else {
+            debug!("Removing asset {} from the previous layout", name);
+            std::fs::remove_file(&legacy)?;
+        }
+
+        // Remove the folders left empty, up to the objects folder
+        let mut parent = legacy.parent();
+        while let Some(dir) = parent.filter(|dir| *dir != objects_dir) {
+            if std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+
+    Ok(())
+}
+
+/// The folder where the assets of a legacy assets index are copied by name, if any.
+///
+/// Virtual indexes (`legacy`) are copied into `assets/virtual/<id>`, while the indexes mapped
+/// to the resources (`pre-1.6`) are copied into the `resources` folder of the game directory.
+pub fn legacy_assets_dir(
+    index: &AssetsList,
+    index_id: &str,
+    layout: &Layout,
+    game_dir: impl AsRef<Path>,
+) -> Option<PathBuf> {
+    if index.map_to_resources {
+        Some(game_dir.as_ref().join("resources"))
+    } else if index.is_virtual {
+        Some(layout.virtual_assets_dir(index_id))
+    } else {
+        None
+    }
+}
+
+/// Copy the assets of an index from the objects folder to the given folder, using their
+/// names as paths.
+///
+/// The assets already copied with the right size are skipped.
+pub fn reconstruct_assets(
+    index: &AssetsList,
+    assets_dir: impl AsRef<Path>,
+    target_dir: impl AsRef<Path>,
+) -> Result<(), std::io::Error> {
+    let target_dir = target_dir.as_ref();
+    debug!("Copying the assets to {:?}", target_dir);
+
+    for (name, object) in &index.objects {
+        let source = object_path(&assets_dir, object);
+        let target = target_dir.join(name);
+
+        if std::fs::metadata(&target).is_ok_and(|metadata| metadata.len() == object.size) {
+            continue;
+        }
+
+        trace!("Copying asset {} to {:?}", name, target);
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &target)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconstruct_virtual_assets() {
+        let root = std::env::temp_dir().join("bauxite-assets-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let layout = Layout::new(&root);
+        let game_dir = root.join("game");
+
+        let index: AssetsList = serde_json::from_value(serde_json::json!({
+            "virtual": true,
+            "objects": {
+                "sounds/random/click.ogg": { "hash": "0000000000000000000000000000000000000000", "size": 5 }
+            }
+        }))
+        .unwrap();
+        assert!(index.is_virtual);
+        assert!(!index.map_to_resources);
+
+        let object = index.objects.values().next().unwrap();
+        let source = object_path(layout.assets_dir(), object);
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, b"click").unwrap();
+
+        let target = legacy_assets_dir(&index, "legacy", &layout, &game_dir).unwrap();
+        assert_eq!(target, root.join("assets/virtual/legacy"));
+
+        reconstruct_assets(&index, layout.assets_dir(), &target).unwrap();
+        assert_eq!(
+            std::fs::read(target.join("sounds/random/click.ogg")).unwrap(),
+            b"click"
+        );
+
+        let index: AssetsList = serde_json::from_value(serde_json::json!({
+            "map_to_resources": true,
+            "objects": {}
+        }))
+        .unwrap();
+        assert_eq!(
+            legacy_assets_dir(&index, "pre-1.6", &layout, &game_dir),
+            Some(game_dir.join("resources"))
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn migrate_named_objects() {
+        let root = std::env::temp_dir().join("bauxite-assets-migrate-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let objects = root.join("objects");
+
+        // sha1("click")
+        let hash = "b93ec56608fb302fea7a8019d7c0e8e7239fc033";
+        let index: AssetsList = serde_json::from_value(serde_json::json!({
+            "objects": {
+                "sounds/random/click.ogg": { "hash": hash, "size": 5 },
+                "sounds/random/broken.ogg": { "hash": hash, "size": 5 }
+            }
+        }))
+        .unwrap();
+
+        std::fs::create_dir_all(objects.join("sounds/random")).unwrap();
+        std::fs::write(objects.join("sounds/random/click.ogg"), b"click").unwrap();
+        std::fs::write(objects.join("sounds/random/broken.ogg"), b"broken").unwrap();
+
+        migrate_objects(&index, &root).unwrap();
+
+        assert_eq!(
+            std::fs::read(objects.join(&hash[..2]).join(hash)).unwrap(),
+            b"click"
+        );
+        assert!(!objects.join("sounds").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use tracing::{debug, trace};
+
+use super::{
+    jsons::version_manifest::McVersionManifest, maven::MavenCoordinate, rules::RuleContext,
+};
+
+/// The separator used between classpath entries.
+#[cfg(target_os = "windows")]
+pub const CLASSPATH_SEPARATOR: &str = ";";
+#[cfg(not(target_os = "windows"))]
+pub const CLASSPATH_SEPARATOR: &str = ":";
+
+/// The ordered list of jars given to the JVM with `-cp`.
+#[derive(Debug, Clone, Default)]
+pub struct Classpath {
+    entries: Vec<PathBuf>,
+}
+
+impl Classpath {
+    /// Build the classpath of a version: its libraries, in the manifest order, followed by the
+    /// client jar.
+    ///
+    /// Libraries disallowed by their rules are skipped, and when several libraries share the same
+    /// `group:artifact` (and classifier), only the first one is kept.
+    pub fn from_manifest(
+        manifest: &McVersionManifest,
+        rules: &RuleContext,
+        libraries_dir: impl AsRef<Path>,
+        client_jar: impl Into<PathBuf>,
+    ) -> Self {
+        let libraries_dir = libraries_dir.as_ref();
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+
+        for library in &manifest.libraries {
+            if !rules.allows(&library.rules) {
+                trace!("Library {} is not allowed, skipping it", library.name);
+                continue;
+            }
+
+            let Some(artifact) = library.artifact() else {
+                continue;
+            };
+
+            let key = match library.name.parse::<MavenCoordinate>() {
+                Ok(coordinate) => coordinate.key(),
+                Err(_) => library.name.clone(),
+            };
+
+            if !seen.insert(key) {
+                debug!("Duplicated library {}, skipping it", library.name);
+                continue;
+            }
+
+            entries.push(libraries_dir.join(&artifact.path));
+        }
+
+        entries.push(client_jar.into());
+
+        Classpath { entries }
+    }
+
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+}
+
+impl Display for Classpath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| entry.to_string_lossy())
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", entries.join(CLASSPATH_SEPARATOR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(name: &str, path: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "downloads": {
+                "artifact": { "path": path, "sha1": "", "size": 0, "url": "" }
+            }
+        })
+    }
+
+    #[test]
+    fn deduplicate_libraries() {
+        let manifest: McVersionManifest = serde_json::from_value(serde_json::json!({
+            "assetIndex": { "id": "1.20.4", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1.20.4",
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.20.4",
+            "libraries": [
+                library("org.ow2.asm:asm:9.6", "org/ow2/asm/asm/9.6/asm-9.6.jar"),
+                library("org.lwjgl:lwjgl:3.3.2", "org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2.jar"),
+                library(
+                    "org.lwjgl:lwjgl:3.3.2:natives-linux",
+                    "org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2-natives-linux.jar"
+                ),
+                library("org.ow2.asm:asm:9.3", "org/ow2/asm/asm/9.3/asm-9.3.jar"),
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1",
+                    "downloads": { "classifiers": {} },
+                    "natives": { "linux": "natives-linux" }
+                }
+            ],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 21,
+            "releaseTime": "2023-12-07T12:56:20+00:00",
+            "time": "2023-12-07T12:56:20+00:00",
+            "type": "release"
+        }))
+        .unwrap();
+
+        let classpath = Classpath::from_manifest(
+            &manifest,
+            &RuleContext::current(),
+            "/libraries",
+            "/versions/client.jar",
+        );
+
+        assert_eq!(
+            classpath.entries(),
+            [
+                PathBuf::from("/libraries/org/ow2/asm/asm/9.6/asm-9.6.jar"),
+                PathBuf::from("/libraries/org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2.jar"),
+                PathBuf::from("/libraries/org/lwjgl/lwjgl/3.3.2/lwjgl-3.3.2-natives-linux.jar"),
+                PathBuf::from("/versions/client.jar"),
+            ]
+        );
+    }
+}
@@ -5,6 +5,12 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 pub struct AssetsList {
     pub objects: HashMap<String, AssetObject>,
+    /// The assets must be copied into the `resources` folder of the game directory (pre-1.6).
+    #[serde(default)]
+    pub map_to_resources: bool,
+    /// The assets must be copied into the `virtual/<id>` folder of the assets (1.6 to 1.7.2).
+    #[serde(default, rename = "virtual")]
+    pub is_virtual: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -29,4 +35,12 @@ impl AssetsList {
 
         Ok(assets_list)
     }
+
+    /// The objects of the index, without the duplicates shared by several assets.
+    pub fn unique_objects(&self) -> Vec<&AssetObject> {
+        let mut objects: Vec<&AssetObject> = self.objects.values().collect();
+        objects.sort_by(|a, b| a.hash.cmp(&b.hash));
+        objects.dedup_by(|a, b| a.hash == b.hash);
+        objects
+    }
 }
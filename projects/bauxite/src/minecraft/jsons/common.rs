@@ -11,3 +11,14 @@ pub enum McVersionType {
     #[serde(rename = "old_alpha")]
     OldAlpha,
 }
+
+impl AsRef<str> for McVersionType {
+    fn as_ref(&self) -> &str {
+        match self {
+            McVersionType::Release => "release",
+            McVersionType::Snapshot => "snapshot",
+            McVersionType::OldBeta => "old_beta",
+            McVersionType::OldAlpha => "old_alpha",
+        }
+    }
+}
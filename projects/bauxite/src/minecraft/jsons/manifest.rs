@@ -21,6 +21,12 @@ pub struct Version {
     #[serde(rename = "type")]
     pub version_type: McVersionType,
     pub url: String,
+    /// The SHA1 of the version JSON (only in the v2 manifest)
+    #[serde(default)]
+    pub sha1: Option<String>,
+    /// 1 if the version supports the latest player safety features, 0 otherwise
+    #[serde(rename = "complianceLevel", default)]
+    pub compliance_level: u8,
     #[serde(rename = "time", with = "time::serde::rfc3339")]
     pub time: OffsetDateTime,
     #[serde(rename = "releaseTime", with = "time::serde::rfc3339")]
@@ -1,4 +1,5 @@
 pub mod assets_index;
 pub mod common;
 pub mod manifest;
+pub mod partial_version_manifest;
 pub mod version_manifest;
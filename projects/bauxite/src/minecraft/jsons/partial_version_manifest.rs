@@ -0,0 +1,263 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::minecraft::maven::MavenCoordinate;
+
+use super::{
+    common::McVersionType,
+    version_manifest::{
+        Arguments, AssetIndexArtifact, Downloads, JavaVersion, Library, LibraryDownloads, Logging,
+        McVersionManifest,
+    },
+};
+
+/// A version JSON inheriting from another version (`inheritsFrom`), as written by the mod
+/// loaders installers: every field missing from it is taken from its parent.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PartialVersionManifest {
+    /// The version ID.
+    pub id: String,
+    /// The ID of the parent version.
+    #[serde(rename = "inheritsFrom")]
+    pub inherits_from: Option<String>,
+    pub arguments: Option<Arguments>,
+    #[serde(rename = "assetIndex")]
+    pub asset_index: Option<AssetIndexArtifact>,
+    pub assets: Option<String>,
+    #[serde(rename = "complianceLevel")]
+    pub compliance_level: Option<u8>,
+    pub downloads: Option<Downloads>,
+    pub jar: Option<String>,
+    #[serde(rename = "javaVersion")]
+    pub java_version: Option<JavaVersion>,
+    /// The libraries added to the parent ones.
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+    pub logging: Option<Logging>,
+    #[serde(rename = "mainClass")]
+    pub main_class: Option<String>,
+    #[serde(rename = "minimumLauncherVersion")]
+    pub minimum_launcher_version: Option<u32>,
+    #[serde(
+        rename = "releaseTime",
+        deserialize_with = "crate::utils::serde::deserialize_optional_datetime",
+        default
+    )]
+    pub release_time: Option<OffsetDateTime>,
+    #[serde(
+        deserialize_with = "crate::utils::serde::deserialize_optional_datetime",
+        default
+    )]
+    pub time: Option<OffsetDateTime>,
+    #[serde(rename = "type")]
+    pub version_type: Option<McVersionType>,
+    /// Replaces the `minecraftArguments` of the parent.
+    #[serde(rename = "minecraftArguments")]
+    pub minecraft_arguments: Option<String>,
+}
+
+impl PartialVersionManifest {
+    /// Merge this version into its parent.
+    ///
+    /// The libraries of this version come first and replace the parent libraries of the same
+    /// `group:artifact`, the arguments are appended to the parent ones, and the client jar is
+    /// the parent one unless this version has its own downloads.
+    ///
+    /// The libraries listed again without download information, such as the natives in the
+    /// legacy Forge versions, keep the downloads of the parent library with the same name.
+    pub fn merge(self, parent: &McVersionManifest) -> McVersionManifest {
+        let parent = parent.clone();
+
+        let parent_downloads: HashMap<&str, &LibraryDownloads> = parent
+            .libraries
+            .iter()
+            .filter_map(|library| Some((library.name.as_str(), library.downloads.as_ref()?)))
+            .collect();
+        let children: Vec<Library> = self
+            .libraries
+            .into_iter()
+            .map(|mut library| {
+                if library.downloads.is_none() {
+                    library.downloads = parent_downloads
+                        .get(library.name.as_str())
+                        .cloned()
+                        .cloned();
+                }
+                library
+            })
+            .collect();
+
+        let keys: HashSet<String> = children.iter().map(library_key).collect();
+        let libraries = children
+            .into_iter()
+            .chain(
+                parent
+                    .libraries
+                    .into_iter()
+                    .filter(|library| !keys.contains(&library_key(library))),
+            )
+            .collect();
+
+        let arguments = match (parent.arguments, self.arguments) {
+            (Some(mut parent), Some(child)) => {
+                parent.game.extend(child.game);
+                parent.jvm.extend(child.jvm);
+                Some(parent)
+            }
+            (parent, child) => child.or(parent),
+        };
+
+        let jar = match (self.jar, &self.downloads) {
+            (Some(jar), _) => Some(jar),
+            (None, Some(_)) => None,
+            (None, None) => Some(parent.jar.unwrap_or(parent.id)),
+        };
+
+        McVersionManifest {
+            arguments,
+            asset_index: self.asset_index.unwrap_or(parent.asset_index),
+            assets: self.assets.unwrap_or(parent.assets),
+            compliance_level: self.compliance_level.unwrap_or(parent.compliance_level),
+            downloads: self.downloads.unwrap_or(parent.downloads),
+            id: self.id,
+            jar,
+            java_version: self.java_version.unwrap_or(parent.java_version),
+            libraries,
+            logging: self.logging.or(parent.logging),
+            main_class: self.main_class.unwrap_or(parent.main_class),
+            minimum_launcher_version: self
+                .minimum_launcher_version
+                .unwrap_or(parent.minimum_launcher_version),
+            release_time: self.release_time.unwrap_or(parent.release_time),
+            time: self.time.unwrap_or(parent.time),
+            version_type: self.version_type.unwrap_or(parent.version_type),
+            minecraft_arguments: self.minecraft_arguments.or(parent.minecraft_arguments),
+        }
+    }
+}
+
+/// The `group:artifact[:classifier]` of a library, or its name if it is not a maven coordinate.
+fn library_key(library: &Library) -> String {
+    match library.name.parse::<MavenCoordinate>() {
+        Ok(coordinate) => coordinate.key(),
+        Err(_) => library.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_parent_natives() {
+        let parent: McVersionManifest = serde_json::from_value(serde_json::json!({
+            "assetIndex": { "id": "1.7.10", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1.7.10",
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.7.10",
+            "libraries": [
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20130708-debug3",
+                    "downloads": {
+                        "classifiers": {
+                            "natives-linux": {
+                                "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.1-nightly-20130708-debug3/lwjgl-platform-2.9.1-nightly-20130708-debug3-natives-linux.jar",
+                                "sha1": "931074f46c795d2f7b30ed6395df5715cfd7675b",
+                                "size": 578680,
+                                "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/lwjgl-platform/2.9.1-nightly-20130708-debug3/lwjgl-platform-2.9.1-nightly-20130708-debug3-natives-linux.jar"
+                            }
+                        }
+                    },
+                    "extract": { "exclude": ["META-INF/"] },
+                    "natives": { "linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows" }
+                },
+                {
+                    "name": "net.java.jinput:jinput-platform:2.0.5",
+                    "downloads": {
+                        "classifiers": {
+                            "natives-linux": {
+                                "path": "net/java/jinput/jinput-platform/2.0.5/jinput-platform-2.0.5-natives-linux.jar",
+                                "sha1": "7ff832a6eb9ab6a767f1ade2b548092d0fa64795",
+                                "size": 10362,
+                                "url": "https://libraries.minecraft.net/net/java/jinput/jinput-platform/2.0.5/jinput-platform-2.0.5-natives-linux.jar"
+                            }
+                        }
+                    },
+                    "extract": { "exclude": ["META-INF/"] },
+                    "natives": { "linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows" }
+                },
+                {
+                    "name": "com.google.guava:guava:15.0",
+                    "downloads": {
+                        "artifact": {
+                            "path": "com/google/guava/guava/15.0/guava-15.0.jar",
+                            "sha1": "ed727a8d9f247e2050281cb083f1c77b09dcb5cd",
+                            "size": 2176558,
+                            "url": "https://libraries.minecraft.net/com/google/guava/guava/15.0/guava-15.0.jar"
+                        }
+                    }
+                }
+            ],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minecraftArguments": "--username ${auth_player_name}",
+            "minimumLauncherVersion": 13,
+            "releaseTime": "2014-05-14T17:29:23+00:00",
+            "time": "2014-05-14T17:29:23+00:00",
+            "type": "release"
+        }))
+        .unwrap();
+
+        // The legacy Forge versions list the game libraries again, without their downloads
+        let child: PartialVersionManifest = serde_json::from_value(serde_json::json!({
+            "id": "1.7.10-Forge10.13.4.1614-1.7.10",
+            "inheritsFrom": "1.7.10",
+            "libraries": [
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20130708-debug3",
+                    "extract": { "exclude": ["META-INF/"] },
+                    "natives": { "linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows" }
+                },
+                {
+                    "name": "net.java.jinput:jinput-platform:2.0.5",
+                    "extract": { "exclude": ["META-INF/"] },
+                    "natives": { "linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows" }
+                },
+                { "name": "com.google.guava:guava:17.0" }
+            ]
+        }))
+        .unwrap();
+
+        let merged = child.merge(&parent);
+
+        let names = merged
+            .libraries
+            .iter()
+            .map(|library| library.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20130708-debug3",
+                "net.java.jinput:jinput-platform:2.0.5",
+                "com.google.guava:guava:17.0"
+            ]
+        );
+
+        for library in &merged.libraries[..2] {
+            let classifiers = library
+                .downloads
+                .as_ref()
+                .and_then(|downloads| downloads.classifiers.as_ref())
+                .unwrap();
+            assert!(classifiers["natives-linux"]
+                .artifact
+                .url
+                .starts_with("https://libraries.minecraft.net/"));
+        }
+
+        // Another version of a library does not take the parent downloads
+        assert!(merged.libraries[2].downloads.is_none());
+    }
+}
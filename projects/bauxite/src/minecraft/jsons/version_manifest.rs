@@ -3,75 +3,91 @@ use std::collections::HashMap;
 use serde::Deserialize;
 use time::OffsetDateTime;
 
-use crate::utils::os::{OperatingSystem, OsName};
+use crate::{minecraft::maven::MavenCoordinate, utils::os::OsName};
 
 use super::common::McVersionType;
 
+/// The repository of the libraries without download information nor URL.
+const MOJANG_LIBRARIES: &str = "https://libraries.minecraft.net/";
+
 /// The Minecraft version root manifest json.
 #[derive(Deserialize, Debug, Clone)]
 pub struct McVersionManifest {
     /// Minecraft launcher [`Arguments`] field.
     /// This field appear in 1.13 (17w43a) and replace the [`McVersionManifest::minecraft_arguments`] field.
-    arguments: Option<Arguments>,
+    pub arguments: Option<Arguments>,
     /// The game assets index json
     #[serde(rename = "assetIndex")]
     pub asset_index: AssetIndexArtifact,
     /// The assets version
-    assets: String,
+    pub assets: String,
     /// Its value is 1 for all recent versions of the game (1.16.4 and above) or 0 for all others.
     /// This tag tells the launcher whether it should urge the user to be careful since this version
     /// is older and might not support the latest player safety features.
     #[serde(rename = "complianceLevel", default)]
-    compliance_level: u8,
+    pub compliance_level: u8,
     /// The Minecraft version downloads json.
     pub downloads: Downloads,
     /// The Minecraft version ID.
     pub id: String,
+    /// The version whose client jar is used, when it is not the version itself (inherited
+    /// versions use the jar of their parent).
+    #[serde(default)]
+    pub jar: Option<String>,
     /// The version of the Java Runtime Environment
     #[serde(rename = "javaVersion", default)]
-    java_version: JavaVersion,
+    pub java_version: JavaVersion,
 
     /// The Minecraft version libraries json.
     pub libraries: Vec<Library>,
 
     /// Logging information for Log4j configuration
-    logging: Option<Logging>,
+    pub logging: Option<Logging>,
 
     /// The main game class; for modern versions, it is `net.minecraft.client.main.Main`, but it may differ from older or ancient versions.
     #[serde(rename = "mainClass")]
-    main_class: String,
+    pub main_class: String,
 
     /// The minimum Launcher version that can run this version of the game.
     #[serde(rename = "minimumLauncherVersion")]
-    minimum_launcher_version: u32,
+    pub minimum_launcher_version: u32,
 
     /// The release date and time.
     #[serde(rename = "releaseTime", with = "time::serde::rfc3339")]
-    release_time: OffsetDateTime,
+    pub release_time: OffsetDateTime,
 
     /// Same as `releaseTime`
     #[serde(with = "time::serde::rfc3339")]
-    time: OffsetDateTime,
+    pub time: OffsetDateTime,
 
     /// The type of this game version. It is shown in the version list when you create new installation. The default values are
     /// [`McVersionType::Release`] (`release`) and [`McVersionType::Snapshot`] (`snapshot`).
     #[serde(rename = "type")]
-    version_type: McVersionType,
+    pub version_type: McVersionType,
 
     // LEGACY FIELDS
     /// The Minecraft launcher `minecraftArguments` field.
     /// This field is deprecated in 1.13 (17w43a) and replaced by the [`McVersionManifest::arguments`] field.
     #[serde(rename = "minecraftArguments")]
-    minecraft_arguments: Option<String>,
+    pub minecraft_arguments: Option<String>,
+}
+
+impl McVersionManifest {
+    /// The ID of the version whose client jar is used.
+    pub fn jar(&self) -> &str {
+        self.jar.as_deref().unwrap_or(&self.id)
+    }
 }
 
 /// The Minecraft launcher arguments json.
 #[derive(Deserialize, Debug, Clone)]
 pub struct Arguments {
     /// The game arguments.
-    game: Vec<Argument<GameRule>>,
+    #[serde(default)]
+    pub game: Vec<Argument<GameRule>>,
     /// The jvm arguments.
-    jvm: Vec<Argument<OsRule>>,
+    #[serde(default)]
+    pub jvm: Vec<Argument<OsRule>>,
 }
 
 /// The Minecraft argument json.
@@ -97,10 +113,10 @@ pub enum Argument<Rule> {
 #[derive(Deserialize, Debug, Clone)]
 pub struct GameRule {
     /// The action to perform.
-    action: String,
+    pub action: RuleAction,
     /// The feature to check.
     #[serde(default)]
-    features: HashMap<String, bool>,
+    pub features: HashMap<String, bool>,
 }
 
 /// The Minecraft game asset index json.
@@ -130,8 +146,11 @@ pub struct Downloads {
 #[derive(Deserialize, Debug, Clone)]
 pub struct Artifact {
     /// The SHA1 of the artifact
-    pub sha1: String,
-    /// The size of the artifact
+    /// Always present in the Mojang JSONs, but may be missing from third-party ones.
+    #[serde(default)]
+    pub sha1: Option<String>,
+    /// The size of the artifact, 0 if unknown
+    #[serde(default)]
     pub size: u64,
     /// The URL where the artifact is hosted
     pub url: String,
@@ -152,10 +171,10 @@ pub struct ArtifactFile {
 #[derive(Deserialize, Debug, Clone)]
 pub struct JavaVersion {
     //// Its value for all 1.17 snapshots is "jre-legacy" until 21w18a, and "java-runtime-alpha" since 21w19a.
-    component: String,
+    pub component: String,
     /// Its value for all 1.17 snapshots is 8 until 21w18a, 16 until since 1.18-pre1 and 17 since 1.18-pre2.
     #[serde(rename = "majorVersion")]
-    major_version: u32,
+    pub major_version: u32,
 }
 
 impl Default for JavaVersion {
@@ -171,11 +190,19 @@ impl Default for JavaVersion {
 #[derive(Deserialize, Debug, Clone)]
 pub struct Library {
     //// The library's download information
-    pub downloads: LibraryDownloads,
+    /// Omitted by the loaders JSONs, the artifact is then found in the [`Library::url`] repository.
+    pub downloads: Option<LibraryDownloads>,
     /// The maven name for the library, in the form of `group:artifactId:version`
     pub name: String,
-    /// The library's URL of the Maven repository (mainly used by Forge)
+    /// The library's URL of the Maven repository (mainly used by Forge and Fabric)
     pub url: Option<String>,
+    /// The SHA1 of the artifact, when there is no download information
+    pub sha1: Option<String>,
+    /// The size of the artifact, when there is no download information
+    pub size: Option<u64>,
+    /// The accepted SHA1s of the artifact (legacy Forge), it was published in several builds
+    #[serde(default)]
+    pub checksums: Vec<String>,
     /// Information about native libraries (in C) bundled with this library. Appears only when there are classifiers for natives
     #[serde(default)]
     pub natives: HashMap<OsName, String>,
@@ -187,21 +214,54 @@ pub struct Library {
     pub rules: Vec<OsRule>,
 }
 
+impl Library {
+    /// The artifact of the library, from its download information or, when there is none, from
+    /// its maven name in the [`Library::url`] repository (the Mojang one by default).
+    pub fn artifact(&self) -> Option<ArtifactFile> {
+        if let Some(downloads) = &self.downloads {
+            return downloads.artifact.clone();
+        }
+
+        let path = self.name.parse::<MavenCoordinate>().ok()?.path();
+        let repository = self.url.as_deref().unwrap_or(MOJANG_LIBRARIES);
+
+        Some(ArtifactFile {
+            artifact: Artifact {
+                sha1: self.sha1.clone(),
+                size: self.size.unwrap_or_default(),
+                url: format!("{}/{}", repository.trim_end_matches('/'), path),
+            },
+            path,
+        })
+    }
+}
+
 /// The os rule
 #[derive(Deserialize, Debug, Clone)]
 pub struct OsRule {
     /// The action to perform
     pub action: RuleAction,
     /// The OS to check
-    #[serde(default)]
-    pub os: HashMap<String, String>,
+    /// Omit to match every OS
+    pub os: Option<OsCondition>,
+}
+
+/// The OS condition of an [`OsRule`]
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct OsCondition {
+    /// The OS name
+    pub name: Option<OsName>,
+    /// A regex matched against the OS version
+    pub version: Option<String>,
+    /// The architecture (`x86`, `x86_64`, `arm64`...)
+    pub arch: Option<String>,
 }
 
 /// The rule action
-/// This is used in the [`LibraryRule`] struct.
-#[derive(Deserialize, Debug, Clone)]
+/// This is used in the [`OsRule`] and [`GameRule`] structs.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RuleAction {
-    /// The rule action to exclude the library
+    /// The rule action to include the library
     #[serde(rename = "allow")]
     Allow,
     /// The rule action to exclude the library
@@ -209,9 +269,9 @@ pub enum RuleAction {
     Disallow,
 }
 
-impl Into<bool> for RuleAction {
-    fn into(self) -> bool {
-        match self {
+impl From<RuleAction> for bool {
+    fn from(action: RuleAction) -> Self {
+        match action {
             RuleAction::Allow => true,
             RuleAction::Disallow => false,
         }
@@ -233,33 +293,33 @@ pub struct LibraryDownloads {
 #[derive(Deserialize, Debug, Clone)]
 pub struct Extract {
     /// Show what to exclude from the extraction
-    exclude: Vec<String>,
+    pub exclude: Vec<String>,
 }
 
 /// Logging information for Log4j configuration
 #[derive(Deserialize, Debug, Clone)]
 pub struct Logging {
-    client: LoggingClient,
+    pub client: LoggingClient,
 }
 
 /// Logging information for Log4j configuration
 #[derive(Deserialize, Debug, Clone)]
 pub struct LoggingClient {
     /// The JVM argument for adding the log configuration. Its value is "-Dlog4j.configurationFile=${path}"
-    argument: String,
+    pub argument: String,
     /// The Log4j2 XML configuration used by this version for hte launcher for launcher's log screen.
-    file: LoggingArtifact,
+    pub file: LoggingArtifact,
     /// Its value is log4j2.xml
     #[serde(rename = "type")]
-    log_type: String,
+    pub log_type: String,
 }
 
 /// Logging information for Log4j configuration
 #[derive(Deserialize, Debug, Clone)]
 pub struct LoggingArtifact {
     /// The artifact ID
-    id: String,
+    pub id: String,
     /// The artifact download information
     #[serde(flatten)]
-    artifact: Artifact,
+    pub artifact: Artifact,
 }
@@ -0,0 +1,269 @@
+use std::path::PathBuf;
+
+use tracing::debug;
+
+use crate::layout::Layout;
+
+use super::{
+    jsons::{
+        common::McVersionType, partial_version_manifest::PartialVersionManifest,
+        version_manifest::McVersionManifest,
+    },
+    version::MinecraftVersion,
+};
+
+/// A builder for creating a [`LocalVersion`].
+pub struct LocalVersionBuilder {
+    id: String,
+    layout: Layout,
+}
+
+impl LocalVersionBuilder {
+    /// Create a new builder for the version with the given ID.
+    pub fn new(id: &str) -> Self {
+        debug!("Creating LocalVersionBuilder with version: {}", id);
+        LocalVersionBuilder {
+            id: id.to_string(),
+            layout: Layout::default(),
+        }
+    }
+
+    /// Set the layout the version JSONs are read from (defaults to [`Layout::default`]).
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Read the version JSON, and merge it into its parents if it inherits from other versions.
+    pub fn build(self) -> Result<LocalVersion, LocalVersionError> {
+        debug!("Building LocalVersion");
+        let manifest = load_manifest(&self.layout, &self.id, &mut Vec::new())?;
+
+        Ok(LocalVersion {
+            name: self.id.clone(),
+            json_path: self.layout.version_json(&self.id),
+            json_url: self
+                .layout
+                .version_json(&self.id)
+                .to_string_lossy()
+                .to_string(),
+            id: self.id,
+            version: manifest,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LocalVersionError {
+    #[error("Failed to read the version JSON of {0}")]
+    ReadJson(String, #[source] std::io::Error),
+    #[error("Failed to parse the version JSON of {0}")]
+    ParseJson(String, #[source] serde_json::Error),
+    #[error("The version {0} inherits from itself")]
+    InheritanceCycle(String),
+}
+
+/// Load a version JSON, resolving its `inheritsFrom` chain.
+///
+/// `children` are the versions inheriting from this one, to detect the cycles.
+fn load_manifest(
+    layout: &Layout,
+    id: &str,
+    children: &mut Vec<String>,
+) -> Result<McVersionManifest, LocalVersionError> {
+    let path = layout.version_json(id);
+    debug!("Reading version JSON: {:?}", path);
+
+    let json =
+        std::fs::read_to_string(&path).map_err(|e| LocalVersionError::ReadJson(id.into(), e))?;
+    let partial: PartialVersionManifest =
+        serde_json::from_str(&json).map_err(|e| LocalVersionError::ParseJson(id.into(), e))?;
+
+    let Some(parent_id) = partial.inherits_from.clone() else {
+        return serde_json::from_str(&json).map_err(|e| LocalVersionError::ParseJson(id.into(), e));
+    };
+
+    if parent_id == id || children.contains(&parent_id) {
+        return Err(LocalVersionError::InheritanceCycle(parent_id));
+    }
+
+    debug!("Version {} inherits from {}", id, parent_id);
+    children.push(id.to_string());
+    let parent = load_manifest(layout, &parent_id, children)?;
+
+    Ok(partial.merge(&parent))
+}
+
+/// A version read from its JSON in the versions folder, such as the versions installed by the
+/// mod loaders or hand-made ones.
+#[derive(Debug)]
+pub struct LocalVersion {
+    name: String,
+    id: String,
+    /// The version, merged with its parents
+    version: McVersionManifest,
+    /// The path to the version JSON
+    json_path: PathBuf,
+    json_url: String,
+}
+
+impl LocalVersion {
+    /// The path to the version JSON.
+    pub fn json_path(&self) -> &PathBuf {
+        &self.json_path
+    }
+}
+
+impl MinecraftVersion for LocalVersion {
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.version.version_type == McVersionType::Snapshot
+    }
+
+    fn json_url(&self) -> &String {
+        &self.json_url
+    }
+
+    fn manifest(&self) -> &McVersionManifest {
+        &self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_json(layout: &Layout, id: &str, json: serde_json::Value) {
+        std::fs::create_dir_all(layout.version_dir(id)).unwrap();
+        std::fs::write(layout.version_json(id), json.to_string()).unwrap();
+    }
+
+    #[test]
+    fn merge_inherited_versions() {
+        let root = std::env::temp_dir().join("bauxite-local-version-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let layout = Layout::new(&root);
+
+        write_json(
+            &layout,
+            "1.20.4",
+            serde_json::json!({
+                "arguments": { "game": ["--username", "${auth_player_name}"], "jvm": ["-cp", "${classpath}"] },
+                "assetIndex": { "id": "12", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+                "assets": "12",
+                "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+                "id": "1.20.4",
+                "libraries": [
+                    {
+                        "name": "org.ow2.asm:asm:9.3",
+                        "downloads": {
+                            "artifact": { "path": "org/ow2/asm/asm/9.3/asm-9.3.jar", "sha1": "", "size": 0, "url": "" }
+                        }
+                    },
+                    {
+                        "name": "com.mojang:brigadier:1.2.9",
+                        "downloads": {
+                            "artifact": { "path": "com/mojang/brigadier/1.2.9/brigadier-1.2.9.jar", "sha1": "", "size": 0, "url": "" }
+                        }
+                    }
+                ],
+                "mainClass": "net.minecraft.client.main.Main",
+                "minimumLauncherVersion": 21,
+                "releaseTime": "2023-12-07T12:56:20+00:00",
+                "time": "2023-12-07T12:56:20+00:00",
+                "type": "release"
+            }),
+        );
+        write_json(
+            &layout,
+            "fabric-loader-0.15.3-1.20.4",
+            serde_json::json!({
+                "id": "fabric-loader-0.15.3-1.20.4",
+                "inheritsFrom": "1.20.4",
+                "arguments": { "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "] },
+                "libraries": [
+                    { "name": "org.ow2.asm:asm:9.6", "url": "https://maven.fabricmc.net/" },
+                    {
+                        "name": "net.fabricmc:fabric-loader:0.15.3",
+                        "url": "https://maven.fabricmc.net/",
+                        "sha1": "0123456789abcdef0123456789abcdef01234567",
+                        "size": 1024
+                    }
+                ],
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient"
+            }),
+        );
+
+        let version = LocalVersionBuilder::new("fabric-loader-0.15.3-1.20.4")
+            .with_layout(layout.clone())
+            .build()
+            .unwrap();
+        let manifest = version.manifest();
+
+        assert_eq!(manifest.id, "fabric-loader-0.15.3-1.20.4");
+        assert_eq!(manifest.jar(), "1.20.4");
+        assert_eq!(manifest.asset_index.id, "12");
+        assert_eq!(
+            manifest.main_class,
+            "net.fabricmc.loader.impl.launch.knot.KnotClient"
+        );
+
+        let arguments = manifest.arguments.as_ref().unwrap();
+        assert_eq!(arguments.game.len(), 2);
+        assert_eq!(arguments.jvm.len(), 3);
+
+        // The loader libraries come first and replace the vanilla ones
+        let names: Vec<&str> = manifest.libraries.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "org.ow2.asm:asm:9.6",
+                "net.fabricmc:fabric-loader:0.15.3",
+                "com.mojang:brigadier:1.2.9"
+            ]
+        );
+
+        let loader = manifest.libraries[1].artifact().unwrap();
+        assert_eq!(
+            loader.path,
+            "net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar"
+        );
+        assert_eq!(
+            loader.artifact.url,
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar"
+        );
+        assert_eq!(loader.artifact.size, 1024);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn detect_inheritance_cycle() {
+        let root = std::env::temp_dir().join("bauxite-local-cycle-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let layout = Layout::new(&root);
+
+        write_json(
+            &layout,
+            "a",
+            serde_json::json!({ "id": "a", "inheritsFrom": "b" }),
+        );
+        write_json(
+            &layout,
+            "b",
+            serde_json::json!({ "id": "b", "inheritsFrom": "a" }),
+        );
+
+        let result = LocalVersionBuilder::new("a").with_layout(layout).build();
+        assert!(matches!(result, Err(LocalVersionError::InheritanceCycle(id)) if id == "a"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
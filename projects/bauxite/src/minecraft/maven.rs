@@ -0,0 +1,119 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A maven coordinate, in the form of `group:artifact:version[:classifier][@extension]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MavenCoordinate {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    /// The file extension, `jar` by default
+    pub extension: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid maven coordinate: {0}")]
+pub struct MavenCoordinateError(String);
+
+impl MavenCoordinate {
+    /// The coordinate without its version: `group:artifact[:classifier]`.
+    ///
+    /// Two libraries with the same key are different versions of the same artifact.
+    pub fn key(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("{}:{}:{}", self.group, self.artifact, classifier),
+            None => format!("{}:{}", self.group, self.artifact),
+        }
+    }
+
+    /// The file name of the artifact (e.g. `lwjgl-3.3.1-natives-linux.jar`).
+    pub fn file_name(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!(
+                "{}-{}-{}.{}",
+                self.artifact, self.version, classifier, self.extension
+            ),
+            None => format!("{}-{}.{}", self.artifact, self.version, self.extension),
+        }
+    }
+
+    /// The path of the artifact relative to the root of a maven repository.
+    pub fn path(&self) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.group.replace('.', "/"),
+            self.artifact,
+            self.version,
+            self.file_name()
+        )
+    }
+}
+
+impl FromStr for MavenCoordinate {
+    type Err = MavenCoordinateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, extension) = match s.split_once('@') {
+            Some((name, extension)) => (name, extension.to_string()),
+            None => (s, "jar".to_string()),
+        };
+
+        let parts: Vec<&str> = name.split(':').collect();
+        let (group, artifact, version, classifier) = match parts.as_slice() {
+            [group, artifact, version] => (group, artifact, version, None),
+            [group, artifact, version, classifier] => {
+                (group, artifact, version, Some(classifier.to_string()))
+            }
+            _ => return Err(MavenCoordinateError(s.to_string())),
+        };
+
+        Ok(MavenCoordinate {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            classifier,
+            extension,
+        })
+    }
+}
+
+impl Display for MavenCoordinate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{}", classifier)?;
+        }
+        if self.extension != "jar" {
+            write!(f, "@{}", self.extension)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_coordinate() {
+        let coordinate: MavenCoordinate = "org.lwjgl:lwjgl:3.3.1:natives-linux".parse().unwrap();
+
+        assert_eq!(coordinate.key(), "org.lwjgl:lwjgl:natives-linux");
+        assert_eq!(
+            coordinate.path(),
+            "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar"
+        );
+        assert_eq!(
+            coordinate.to_string(),
+            "org.lwjgl:lwjgl:3.3.1:natives-linux"
+        );
+
+        let coordinate: MavenCoordinate = "de.oceanlabs.mcp:mcp_config:1.20.4@zip".parse().unwrap();
+        assert_eq!(
+            coordinate.path(),
+            "de/oceanlabs/mcp/mcp_config/1.20.4/mcp_config-1.20.4.zip"
+        );
+
+        assert!("invalid".parse::<MavenCoordinate>().is_err());
+    }
+}
@@ -1,8 +1,15 @@
 use std::path::PathBuf;
 
+pub mod arguments;
+pub mod assets;
+pub mod classpath;
+pub mod maven;
+pub mod natives;
+pub mod rules;
 pub mod version;
 
 pub mod jsons;
+pub mod local;
 pub mod vanilla;
 
 /// Returns the path to the official Minecraft folder.
@@ -0,0 +1,332 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use tracing::{debug, trace};
+use zip::ZipArchive;
+
+use super::{
+    jsons::version_manifest::{ArtifactFile, Library, McVersionManifest},
+    maven::MavenCoordinate,
+    rules::RuleContext,
+};
+
+/// The extensions of the files extracted from the natives artifacts.
+const NATIVE_EXTENSIONS: [&str; 4] = ["so", "dll", "dylib", "jnilib"];
+
+/// A library containing native files to extract.
+#[derive(Debug, Clone)]
+pub struct NativeLibrary<'a> {
+    pub library: &'a Library,
+    pub artifact: ArtifactFile,
+    /// Legacy natives are downloaded from the library classifiers (`natives` field) and are
+    /// extracted as is. Since 1.19, natives are regular artifacts with a `natives-*` classifier
+    /// and only their native files are extracted.
+    pub legacy: bool,
+}
+
+impl NativeLibrary<'_> {
+    fn excluded(&self, name: &str) -> bool {
+        if name.starts_with("META-INF/") {
+            return true;
+        }
+
+        match &self.library.extract {
+            Some(extract) => extract
+                .exclude
+                .iter()
+                .any(|exclude| name.starts_with(exclude)),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NativesError {
+    #[error("Failed to read the native library")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to extract the native library")]
+    ZipError(#[from] zip::result::ZipError),
+}
+
+/// List the libraries of a version containing native files for the OS of the context.
+pub fn native_libraries<'a>(
+    manifest: &'a McVersionManifest,
+    rules: &RuleContext,
+) -> Vec<NativeLibrary<'a>> {
+    let mut natives = Vec::new();
+
+    for library in &manifest.libraries {
+        if !rules.allows(&library.rules) {
+            continue;
+        }
+
+        // Legacy natives, downloaded from the classifiers
+        if let Some(classifier) = library.natives.get(&rules.os_name) {
+            let bits = if rules.os_arch == "x86" { "32" } else { "64" };
+            let classifier = classifier.replace("${arch}", bits);
+
+            match library
+                .downloads
+                .as_ref()
+                .and_then(|downloads| downloads.classifiers.as_ref())
+                .and_then(|classifiers| classifiers.get(&classifier))
+            {
+                Some(artifact) => natives.push(NativeLibrary {
+                    library,
+                    artifact: artifact.clone(),
+                    legacy: true,
+                }),
+                None => debug!("No {} classifier for library {}", classifier, library.name),
+            }
+            continue;
+        }
+
+        // Natives shipped as regular artifacts (1.19+), one per architecture
+        let is_native = library
+            .name
+            .parse::<MavenCoordinate>()
+            .ok()
+            .and_then(|coordinate| coordinate.classifier)
+            .is_some_and(|classifier| {
+                classifier.starts_with("natives-") && classifier_arch(&classifier) == rules.os_arch
+            });
+
+        if let (true, Some(artifact)) = (is_native, library.artifact()) {
+            natives.push(NativeLibrary {
+                library,
+                artifact,
+                legacy: false,
+            });
+        }
+    }
+
+    natives
+}
+
+/// The architecture of a `natives-*` classifier, given by its suffix (`natives-windows-arm64`).
+/// Classifiers without a suffix are built for x86_64.
+fn classifier_arch(classifier: &str) -> &str {
+    match classifier.rsplit_once('-') {
+        Some((_, arch @ ("arm64" | "arm32" | "x86"))) => arch,
+        _ => "x86_64",
+    }
+}
+
+/// Extract the native files of a version into the given directory.
+///
+/// The `META-INF/` folder and the entries excluded by the library `extract` field are skipped.
+pub fn extract_natives(
+    manifest: &McVersionManifest,
+    rules: &RuleContext,
+    libraries_dir: impl AsRef<Path>,
+    natives_dir: impl AsRef<Path>,
+) -> Result<(), NativesError> {
+    let natives_dir = natives_dir.as_ref();
+
+    if !natives_dir.exists() {
+        debug!(
+            "Natives folder does not exist, creating it at {:?}",
+            natives_dir
+        );
+        std::fs::create_dir_all(natives_dir)?;
+    }
+
+    for native in native_libraries(manifest, rules) {
+        let path = libraries_dir.as_ref().join(&native.artifact.path);
+        debug!("Extracting native library: {:?}", path);
+
+        extract_native(&native, &path, natives_dir)?;
+    }
+
+    Ok(())
+}
+
+fn extract_native(
+    native: &NativeLibrary,
+    path: &Path,
+    natives_dir: &Path,
+) -> Result<(), NativesError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+
+        if entry.is_dir() || native.excluded(entry.name()) {
+            continue;
+        }
+
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+
+        let output: PathBuf = if native.legacy {
+            natives_dir.join(name)
+        } else {
+            // Only keep the native files, flattened into the natives folder
+            let is_native = name
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| NATIVE_EXTENSIONS.contains(&extension));
+
+            match (is_native, name.file_name()) {
+                (true, Some(file_name)) => natives_dir.join(file_name),
+                _ => continue,
+            }
+        };
+
+        trace!("Extracting {} to {:?}", entry.name(), output);
+
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = File::create(&output)?;
+        std::io::copy(&mut entry, &mut file)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+    use crate::minecraft::rules::OsName;
+
+    fn write_jar(path: &Path, entries: &[&str]) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for entry in entries {
+            zip.start_file(*entry, SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(entry.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn extract_legacy_and_artifact_natives() {
+        let rules = RuleContext::current();
+        let os = &rules.os_name;
+        let root = std::env::temp_dir().join("bauxite-natives-test");
+        let _ = std::fs::remove_dir_all(&root);
+        let libraries = root.join("libraries");
+        let natives = root.join("natives");
+
+        write_jar(
+            &libraries.join("legacy.jar"),
+            &["META-INF/MANIFEST.MF", "excluded/file.txt", "liblwjgl.so"],
+        );
+        write_jar(
+            &libraries.join("modern.jar"),
+            &[
+                "META-INF/MANIFEST.MF",
+                "linux/x64/org/lwjgl/liblwjgl64.so",
+                "lwjgl.sha1",
+            ],
+        );
+
+        let manifest: McVersionManifest = serde_json::from_value(serde_json::json!({
+            "assetIndex": { "id": "1.19", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1.19",
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.19",
+            "libraries": [
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1",
+                    "downloads": {
+                        "classifiers": {
+                            format!("natives-{}", os.as_ref()): {
+                                "path": "legacy.jar", "sha1": "", "size": 0, "url": ""
+                            }
+                        }
+                    },
+                    "natives": { os.as_ref(): format!("natives-{}", os.as_ref()) },
+                    "extract": { "exclude": ["META-INF/", "excluded/"] }
+                },
+                {
+                    "name": "org.lwjgl:lwjgl:3.3.1:natives-linux",
+                    "downloads": {
+                        "artifact": { "path": "modern.jar", "sha1": "", "size": 0, "url": "" }
+                    }
+                }
+            ],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 21,
+            "releaseTime": "2022-06-07T09:42:18+00:00",
+            "time": "2022-06-07T09:42:18+00:00",
+            "type": "release"
+        }))
+        .unwrap();
+
+        extract_natives(&manifest, &rules, &libraries, &natives).unwrap();
+
+        assert!(natives.join("liblwjgl.so").exists());
+        assert!(natives.join("liblwjgl64.so").exists());
+        assert!(!natives.join("META-INF").exists());
+        assert!(!natives.join("excluded").exists());
+        assert!(!natives.join("lwjgl.sha1").exists());
+        assert!(!natives.join("linux").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn classifier_archs() {
+        assert_eq!(classifier_arch("natives-windows"), "x86_64");
+        assert_eq!(classifier_arch("natives-windows-x86"), "x86");
+        assert_eq!(classifier_arch("natives-windows-arm64"), "arm64");
+        assert_eq!(classifier_arch("natives-macos"), "x86_64");
+        assert_eq!(classifier_arch("natives-macos-arm64"), "arm64");
+        assert_eq!(classifier_arch("natives-linux-arm32"), "arm32");
+    }
+
+    #[test]
+    fn skip_natives_of_other_archs() {
+        let library = |classifier: &str| {
+            serde_json::json!({
+                "name": format!("org.lwjgl:lwjgl:3.3.1:{}", classifier),
+                "downloads": {
+                    "artifact": {
+                        "path": format!("lwjgl-3.3.1-{}.jar", classifier),
+                        "sha1": "", "size": 0, "url": ""
+                    }
+                }
+            })
+        };
+
+        let manifest: McVersionManifest = serde_json::from_value(serde_json::json!({
+            "assetIndex": { "id": "1.19", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1.19",
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.19",
+            "libraries": [
+                library("natives-windows"),
+                library("natives-windows-arm64"),
+                library("natives-windows-x86"),
+            ],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 21,
+            "releaseTime": "2022-06-07T09:42:18+00:00",
+            "time": "2022-06-07T09:42:18+00:00",
+            "type": "release"
+        }))
+        .unwrap();
+
+        let rules = RuleContext::new(OsName::Windows, "10.0.19045", "x86_64");
+        let natives = native_libraries(&manifest, &rules);
+
+        assert_eq!(natives.len(), 1);
+        assert_eq!(
+            natives[0].library.name,
+            "org.lwjgl:lwjgl:3.3.1:natives-windows"
+        );
+    }
+}
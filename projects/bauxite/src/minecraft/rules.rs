@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use tracing::debug;
+
+pub use crate::utils::os::OsName;
+use crate::utils::os::{current_arch, current_version};
+
+use super::jsons::version_manifest::{GameRule, OsRule, RuleAction};
+
+/// The environment the library, natives and argument rules are checked against.
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+    /// The OS name
+    pub os_name: OsName,
+    /// The OS version, matched against the `os.version` regexes
+    pub os_version: String,
+    /// The architecture, as named in the Mojang rules (`x86`, `x86_64`, `arm64`...)
+    pub os_arch: String,
+    /// The launcher features (`is_demo_user`, `has_custom_resolution`...)
+    pub features: HashMap<String, bool>,
+}
+
+impl RuleContext {
+    /// Create a context for the current system, without any feature.
+    pub fn current() -> Self {
+        RuleContext {
+            os_name: OsName::current(),
+            os_version: current_version(),
+            os_arch: current_arch().to_string(),
+            features: HashMap::new(),
+        }
+    }
+
+    /// Create a context for the given system, without any feature.
+    pub fn new(os_name: OsName, os_version: &str, os_arch: &str) -> Self {
+        RuleContext {
+            os_name,
+            os_version: os_version.to_string(),
+            os_arch: os_arch.to_string(),
+            features: HashMap::new(),
+        }
+    }
+
+    /// Enable or disable a feature.
+    pub fn with_feature(mut self, feature: &str, enabled: bool) -> Self {
+        self.features.insert(feature.to_string(), enabled);
+        self
+    }
+
+    /// Check if a feature is enabled.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.get(feature).copied().unwrap_or(false)
+    }
+
+    /// Evaluate a list of rules.
+    ///
+    /// An empty list allows everything. Otherwise everything is disallowed by default, and the
+    /// last matching rule decides.
+    pub fn allows<R: Rule>(&self, rules: &[R]) -> bool {
+        if rules.is_empty() {
+            return true;
+        }
+
+        rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(self))
+            .is_some_and(|rule| rule.action() == RuleAction::Allow)
+    }
+}
+
+impl Default for RuleContext {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+/// A rule checked against a [`RuleContext`].
+pub trait Rule {
+    /// The action to apply when the rule matches.
+    fn action(&self) -> RuleAction;
+    /// Check if the conditions of the rule match the context.
+    fn matches(&self, context: &RuleContext) -> bool;
+}
+
+impl Rule for OsRule {
+    fn action(&self) -> RuleAction {
+        self.action
+    }
+
+    fn matches(&self, context: &RuleContext) -> bool {
+        let Some(os) = &self.os else {
+            return true;
+        };
+
+        if let Some(name) = &os.name {
+            if *name != context.os_name {
+                return false;
+            }
+        }
+
+        if let Some(arch) = &os.arch {
+            if *arch != context.os_arch {
+                return false;
+            }
+        }
+
+        if let Some(version) = &os.version {
+            match Regex::new(version) {
+                Ok(regex) if regex.is_match(&context.os_version) => {}
+                Ok(_) => return false,
+                Err(e) => {
+                    debug!("Invalid OS version regex {}: {:?}", version, e);
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Rule for GameRule {
+    fn action(&self) -> RuleAction {
+        self.action
+    }
+
+    fn matches(&self, context: &RuleContext) -> bool {
+        self.features
+            .iter()
+            .all(|(feature, enabled)| context.has_feature(feature) == *enabled)
+    }
+}
@@ -1,7 +1,6 @@
+use bauxite_download::{bytes_sha1, Downloader, FetchError};
 use tracing::debug;
 
-use crate::constants;
-
 use super::{
     jsons::{common::McVersionType, manifest::McVersionsList, version_manifest::McVersionManifest},
     version::MinecraftVersion,
@@ -12,6 +11,7 @@ pub struct VanillaVersionBuilder {
     /// The version of Minecraft to build.
     version: VanillaVersionType,
     snapshot: bool,
+    downloader: Downloader,
 }
 
 enum VanillaVersionType {
@@ -26,6 +26,7 @@ impl VanillaVersionBuilder {
         VanillaVersionBuilder {
             version: VanillaVersionType::Version(version.into()),
             snapshot: false,
+            downloader: Downloader::shared().clone(),
         }
     }
 
@@ -34,16 +35,30 @@ impl VanillaVersionBuilder {
         VanillaVersionBuilder {
             version: VanillaVersionType::Latest,
             snapshot: false,
+            downloader: Downloader::shared().clone(),
         }
     }
 
+    /// Resolve the latest version to the latest snapshot instead of the latest release.
+    pub fn with_snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Set the downloader used to fetch the version JSONs (defaults to [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
     /// Build the [`VanillaVersion`].
     pub async fn build(self) -> Result<VanillaVersion, VanillaVersionError> {
         debug!("Building VanillaVersion");
         // Fetch version json
-        let manifest = fetch_version_json().await?;
+        let manifest = fetch_version_json(&self.downloader).await?;
 
         let version = match self.version {
+            VanillaVersionType::Latest if self.snapshot => manifest.latest.snapshot,
             VanillaVersionType::Latest => manifest.latest.release,
             VanillaVersionType::Version(version) => version.to_string(),
         };
@@ -53,12 +68,15 @@ impl VanillaVersionBuilder {
             None => return Err(VanillaVersionError::VersionNotFound),
         };
 
-        let version_manifest = fetch_version_manifest_json(&version.url).await?;
+        let (version_manifest, json) =
+            fetch_version_manifest_json(&self.downloader, &version.url, version.sha1.as_deref())
+                .await?;
 
         Ok(VanillaVersion {
             name: format!("Vanilla {}", version.id),
             id: version.id.clone(),
             version: version_manifest,
+            json,
             json_url: version.url.clone(),
             snapshot: version.version_type == McVersionType::Snapshot,
         })
@@ -70,20 +88,40 @@ pub enum VanillaVersionError {
     #[error("Version not found")]
     VersionNotFound,
     #[error("Failed to fetch version manifest")]
-    FetchVersionManifest(#[from] reqwest::Error),
+    FetchVersionManifest(#[from] FetchError),
+    #[error("The version JSON does not match the hash of the versions list")]
+    InvalidChecksum,
+    #[error("The version JSON is not valid UTF-8")]
+    InvalidEncoding(#[source] std::string::FromUtf8Error),
 }
 
-pub async fn fetch_version_json() -> Result<McVersionsList, reqwest::Error> {
+/// Fetch the list of the Minecraft versions from the endpoints of the downloader.
+pub async fn fetch_version_json(downloader: &Downloader) -> Result<McVersionsList, FetchError> {
     debug!("Fetching version manifest list");
-    reqwest::get(constants::VANILLA_VERSIONS)
-        .await?
-        .json()
+    downloader
+        .get_json(downloader.endpoints().version_manifest())
         .await
 }
 
-pub async fn fetch_version_manifest_json(url: &str) -> Result<McVersionManifest, reqwest::Error> {
+/// Fetch a version JSON, checking its SHA1 when it is known (v2 versions list).
+///
+/// Returns the parsed version and the JSON as downloaded.
+pub async fn fetch_version_manifest_json(
+    downloader: &Downloader,
+    url: &str,
+    sha1: Option<&str>,
+) -> Result<(McVersionManifest, String), VanillaVersionError> {
     debug!("Fetching version manifest json from {}", url);
-    reqwest::get(url).await?.json().await
+    let json = downloader.get_bytes(url).await?;
+
+    if sha1.is_some_and(|sha1| bytes_sha1(&json) != sha1) {
+        return Err(VanillaVersionError::InvalidChecksum);
+    }
+
+    let json = String::from_utf8(json).map_err(VanillaVersionError::InvalidEncoding)?;
+    let manifest = serde_json::from_str(&json).map_err(FetchError::from)?;
+
+    Ok((manifest, json))
 }
 
 /// The Minecraft version configuration
@@ -92,12 +130,14 @@ pub struct VanillaVersion {
     name: String,
     id: String,
     version: McVersionManifest,
+    /// The version JSON, as downloaded
+    json: String,
     snapshot: bool,
     /// The URL to the version json
     json_url: String,
 }
 
-impl<'a> MinecraftVersion for VanillaVersion {
+impl MinecraftVersion for VanillaVersion {
     fn id(&self) -> &String {
         &self.id
     }
@@ -117,4 +157,8 @@ impl<'a> MinecraftVersion for VanillaVersion {
     fn manifest(&self) -> &McVersionManifest {
         &self.version
     }
+
+    fn raw_json(&self) -> Option<&str> {
+        Some(&self.json)
+    }
 }
@@ -1,9 +1,19 @@
+use std::any::Any;
+
 use super::jsons::version_manifest::McVersionManifest;
 
-pub trait MinecraftVersion {
+pub trait MinecraftVersion: Any {
     fn id(&self) -> &String;
     fn name(&self) -> &String;
     fn is_snapshot(&self) -> bool;
     fn json_url(&self) -> &String;
     fn manifest(&self) -> &McVersionManifest;
+    /// The version JSON as published, persisted next to the client jar by the updater.
+    fn raw_json(&self) -> Option<&str> {
+        None
+    }
+    /// The version this one inherits from, such as the vanilla version of a mod loader.
+    fn parent(&self) -> Option<&dyn MinecraftVersion> {
+        None
+    }
 }
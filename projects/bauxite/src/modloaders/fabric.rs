@@ -0,0 +1,63 @@
+use bauxite_download::{Downloader, Endpoints, FetchError};
+use serde::Deserialize;
+
+use super::meta::{MetaLoader, MetaVersion, MetaVersionBuilder, MetaVersionError};
+
+/// The Fabric loader, served by the Fabric meta API.
+#[derive(Debug)]
+pub struct Fabric;
+
+impl MetaLoader for Fabric {
+    const NAME: &'static str = "Fabric";
+
+    type LoaderVersion = FabricLoaderVersion;
+
+    fn meta_url(endpoints: &Endpoints) -> &str {
+        endpoints.fabric_meta()
+    }
+
+    fn loader_version(version: &FabricLoaderVersion) -> &str {
+        &version.loader.version
+    }
+
+    fn is_stable(version: &FabricLoaderVersion) -> bool {
+        version.loader.stable
+    }
+}
+
+/// A builder for creating a [`FabricVersion`].
+pub type FabricVersionBuilder = MetaVersionBuilder<Fabric>;
+/// A vanilla version with the Fabric loader.
+pub type FabricVersion = MetaVersion<Fabric>;
+pub type FabricVersionError = MetaVersionError;
+
+/// A Fabric loader version, and the intermediary mappings of the game version it was listed for.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FabricLoaderVersion {
+    pub loader: FabricComponent,
+    pub intermediary: FabricComponent,
+}
+
+/// A component of the Fabric meta API (loader, intermediary...).
+#[derive(Deserialize, Debug, Clone)]
+pub struct FabricComponent {
+    /// The maven name of the component
+    pub maven: String,
+    pub version: String,
+    /// Missing for the intermediary mappings of the versions which are not released yet
+    #[serde(default)]
+    pub stable: bool,
+}
+
+/// List the Fabric loader versions compatible with a game version, newest first.
+pub async fn fetch_loader_versions(
+    downloader: &Downloader,
+    game_version: &str,
+) -> Result<Vec<FabricLoaderVersion>, FetchError> {
+    super::meta::fetch_loader_versions(
+        downloader,
+        downloader.endpoints().fabric_meta(),
+        game_version,
+    )
+    .await
+}
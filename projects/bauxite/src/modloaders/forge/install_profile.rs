@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::minecraft::jsons::version_manifest::Library;
+
+/// The `install_profile.json` of the Forge installers (1.12.2-14.23.5.2851 and above).
+#[derive(Deserialize, Debug, Clone)]
+pub struct InstallProfile {
+    /// The version of the format
+    #[serde(default)]
+    pub spec: u32,
+    /// The ID of the installed version
+    pub version: String,
+    /// The vanilla version it is installed on
+    pub minecraft: String,
+    /// The path of the version JSON in the installer
+    #[serde(default = "default_json")]
+    pub json: String,
+    /// The values given to the processors arguments, by name
+    #[serde(default)]
+    pub data: HashMap<String, SidedData>,
+    /// The tasks generating the patched jars
+    #[serde(default)]
+    pub processors: Vec<Processor>,
+    /// The libraries needed by the processors
+    #[serde(default)]
+    pub libraries: Vec<Library>,
+}
+
+fn default_json() -> String {
+    "/version.json".to_string()
+}
+
+/// A value of the install profile data, for each side.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SidedData {
+    pub client: String,
+    pub server: String,
+}
+
+/// A java program run by the installer.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Processor {
+    /// The sides it runs on, all of them if empty
+    #[serde(default)]
+    pub sides: Vec<String>,
+    /// The maven name of the executable jar
+    pub jar: String,
+    /// The maven names of its dependencies
+    #[serde(default)]
+    pub classpath: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The files it generates, and their expected SHA1
+    #[serde(default)]
+    pub outputs: HashMap<String, String>,
+}
+
+impl Processor {
+    /// Check if the processor runs when installing the client.
+    pub fn runs_on_client(&self) -> bool {
+        self.sides.is_empty() || self.sides.iter().any(|side| side == "client")
+    }
+}
@@ -0,0 +1,449 @@
+//! Forge versions from 1.7.10 to 1.12.2, launched through LaunchWrapper
+//! (`net.minecraft.launchwrapper.Launch`) with the `--tweakClass` arguments.
+//!
+//! Their installers only contain the universal jar and a version JSON (`versionInfo`), whose
+//! libraries have no download information.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use bauxite_download::{file_sha1, DownloadInfo, Downloader};
+use serde::Deserialize;
+use serde_json::Value;
+use tempfile::TempDir;
+use tokio::task::JoinSet;
+use tracing::debug;
+use zip::ZipArchive;
+
+use crate::{
+    instance::Instance,
+    layout::Layout,
+    minecraft::{
+        jsons::{
+            partial_version_manifest::PartialVersionManifest,
+            version_manifest::{ArtifactFile, McVersionManifest},
+        },
+        maven::MavenCoordinate,
+        vanilla::{VanillaVersion, VanillaVersionBuilder},
+        version::MinecraftVersion,
+    },
+};
+
+use super::{download_installer, ForgeInstallError, ForgeVersionError, INSTALLER};
+
+/// The former Forge maven, still referenced by the legacy version JSONs.
+const LEGACY_FORGE_MAVEN: [&str; 2] = [
+    "http://files.minecraftforge.net/maven",
+    "https://files.minecraftforge.net/maven",
+];
+/// The repository of the libraries the Forge maven no longer serves as published.
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+/// The `install` section of a legacy install profile.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LegacyInstall {
+    /// The maven name of Forge
+    pub path: String,
+    /// The path of the universal jar in the installer
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// The vanilla version it is installed on
+    pub minecraft: String,
+    /// The ID of the installed version
+    pub target: String,
+}
+
+/// The `install_profile.json` of the legacy installers.
+#[derive(Deserialize, Debug)]
+struct LegacyInstallProfile {
+    install: LegacyInstall,
+    #[serde(rename = "versionInfo")]
+    version_info: Value,
+}
+
+/// A builder for creating a [`LegacyForgeVersion`].
+pub struct LegacyForgeVersionBuilder {
+    game_version: String,
+    forge_version: String,
+    downloader: Downloader,
+}
+
+impl LegacyForgeVersionBuilder {
+    /// Create a new builder for the given game and Forge versions, as found in the name of the
+    /// installer (e.g. `1.7.10` and `10.13.4.1614-1.7.10`, or `1.12.2` and `14.23.5.2847`).
+    pub fn new(game_version: &str, forge_version: &str) -> Self {
+        debug!(
+            "Creating LegacyForgeVersionBuilder with version: {}-{}",
+            game_version, forge_version
+        );
+        LegacyForgeVersionBuilder {
+            game_version: game_version.to_string(),
+            forge_version: forge_version.to_string(),
+            downloader: Downloader::shared().clone(),
+        }
+    }
+
+    /// Set the downloader used to fetch the installer and the libraries (defaults to
+    /// [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Download the installer, and merge its version JSON into the vanilla version.
+    pub async fn build(self) -> Result<LegacyForgeVersion, ForgeVersionError> {
+        debug!("Building LegacyForgeVersion");
+        let coordinate = MavenCoordinate {
+            group: "net.minecraftforge".to_string(),
+            artifact: "forge".to_string(),
+            version: format!("{}-{}", self.game_version, self.forge_version),
+            classifier: Some("installer".to_string()),
+            extension: "jar".to_string(),
+        };
+        let forge_maven = self.downloader.endpoints().forge_maven().to_string();
+        let (installer_dir, url) =
+            download_installer(&coordinate, &forge_maven, &self.downloader).await?;
+
+        let LegacyInstallProfile {
+            install,
+            mut version_info,
+        } = {
+            let installer = installer_dir.path().join(INSTALLER);
+            tokio::task::spawn_blocking(move || read_legacy_installer(&installer)).await??
+        };
+
+        normalize_libraries(&mut version_info, &install, &forge_maven);
+        // The oldest version JSONs repeat the vanilla one instead of inheriting from it
+        if let Some(version_info) = version_info.as_object_mut() {
+            version_info
+                .entry("inheritsFrom")
+                .or_insert_with(|| Value::String(install.minecraft.clone()));
+        }
+        let json = version_info.to_string();
+        let manifest: PartialVersionManifest = serde_json::from_value(version_info)?;
+
+        let vanilla = VanillaVersionBuilder::new(&install.minecraft)
+            .with_downloader(self.downloader.clone())
+            .build()
+            .await?;
+
+        Ok(LegacyForgeVersion {
+            name: format!("Forge {} ({})", self.game_version, self.forge_version),
+            id: manifest.id.clone(),
+            forge_version: self.forge_version,
+            version: manifest.merge(vanilla.manifest()),
+            json,
+            json_url: url,
+            vanilla,
+            install,
+            installer_dir,
+            downloader: self.downloader,
+        })
+    }
+}
+
+fn read_legacy_installer(installer: &Path) -> Result<LegacyInstallProfile, ForgeVersionError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(installer)?))?;
+
+    let mut profile = String::new();
+    archive
+        .by_name("install_profile.json")?
+        .read_to_string(&mut profile)?;
+
+    Ok(serde_json::from_str(&profile)?)
+}
+
+/// Fix the libraries of a legacy version JSON for the client:
+/// - the libraries not needed by the client (`clientreq` false) are removed, the ones flagged
+///   with `serverreq` only being needed by both sides,
+/// - the former Forge maven is replaced by the current one,
+/// - Forge itself is marked as installed from the installer, as the maven only has its
+///   universal jar.
+fn normalize_libraries(version_info: &mut Value, install: &LegacyInstall, forge_maven: &str) {
+    let Some(libraries) = version_info
+        .get_mut("libraries")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    libraries.retain(|library| library.get("clientreq").and_then(Value::as_bool) != Some(false));
+
+    for library in libraries.iter_mut().filter_map(Value::as_object_mut) {
+        let name = library.get("name").and_then(Value::as_str).unwrap_or("");
+
+        if name == install.path {
+            if let Ok(coordinate) = name.parse::<MavenCoordinate>() {
+                library.remove("url");
+                library.insert(
+                    "downloads".to_string(),
+                    serde_json::json!({ "artifact": { "path": coordinate.path(), "url": "" } }),
+                );
+            }
+            continue;
+        }
+
+        let url = library.get("url").and_then(Value::as_str).unwrap_or("");
+        if let Some(path) = LEGACY_FORGE_MAVEN
+            .iter()
+            .find_map(|legacy| url.strip_prefix(legacy))
+        {
+            let url = format!("{}{}", forge_maven, path);
+            library.insert("url".to_string(), Value::String(url));
+        }
+    }
+}
+
+/// A vanilla version with a legacy Forge.
+///
+/// The universal jar and the Forge libraries are installed by [`LegacyForgeVersion::install`],
+/// which must be run before the [`Updater`](crate::Updater).
+#[derive(Debug)]
+pub struct LegacyForgeVersion {
+    name: String,
+    id: String,
+    forge_version: String,
+    /// The Forge version JSON, merged into the vanilla version
+    version: McVersionManifest,
+    /// The Forge version JSON, with its libraries fixed for the client
+    json: String,
+    /// The URL to the installer
+    json_url: String,
+    vanilla: VanillaVersion,
+    install: LegacyInstall,
+    /// The directory of the downloaded installer
+    installer_dir: TempDir,
+    downloader: Downloader,
+}
+
+impl LegacyForgeVersion {
+    /// The version of Forge.
+    pub fn forge_version(&self) -> &str {
+        &self.forge_version
+    }
+
+    /// The vanilla version Forge is installed on.
+    pub fn vanilla(&self) -> &VanillaVersion {
+        &self.vanilla
+    }
+
+    /// Install the legacy Forge version of an instance: extract the universal jar from the
+    /// installer, and download the libraries of the Forge maven into the layout of the instance.
+    ///
+    /// The `checksums` of these libraries are the hashes of the builds published at the time:
+    /// when the Forge maven serves a jar matching none of them, it is downloaded from Maven
+    /// Central instead.
+    pub async fn install(instance: &Instance) -> Result<(), ForgeInstallError> {
+        let forge = instance
+            .version::<LegacyForgeVersion>()
+            .ok_or(ForgeInstallError::NotForge)?;
+
+        forge.install_libraries(instance.layout()).await
+    }
+
+    async fn install_libraries(&self, layout: &Layout) -> Result<(), ForgeInstallError> {
+        debug!("Installing Forge {}", self.id);
+        let libraries_dir = layout.libraries_dir();
+
+        let universal = libraries_dir.join(self.install.path.parse::<MavenCoordinate>()?.path());
+        {
+            let installer = self.installer_dir.path().join(INSTALLER);
+            let file_path = self.install.file_path.clone();
+            tokio::task::spawn_blocking(move || {
+                extract_universal(&installer, &file_path, &universal)
+            })
+            .await??;
+        }
+
+        let mut joinset = JoinSet::new();
+
+        for library in &self.version.libraries {
+            if library.url.is_none() {
+                continue;
+            }
+            let Some(artifact) = library.artifact() else {
+                continue;
+            };
+            if artifact.artifact.url.is_empty() {
+                continue;
+            }
+
+            let checksums = library.checksums.clone();
+            let libraries_dir = libraries_dir.clone();
+            let downloader = self.downloader.clone();
+            joinset.spawn(async move {
+                download_library(artifact, checksums, &libraries_dir, &downloader).await
+            });
+        }
+
+        while let Some(result) = joinset.join_next().await {
+            let path = result??;
+            debug!("Downloaded Forge library: {:?}", path);
+        }
+
+        Ok(())
+    }
+}
+
+fn extract_universal(
+    installer: &Path,
+    file_path: &str,
+    output: &Path,
+) -> Result<(), ForgeInstallError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(installer)?))?;
+    let mut entry = archive.by_name(file_path)?;
+
+    debug!("Extracting {} to {:?}", file_path, output);
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::io::copy(&mut entry, &mut File::create(output)?)?;
+
+    Ok(())
+}
+
+/// Download a library from its repository, or from Maven Central if it matches none of its
+/// checksums.
+///
+/// The libraries already present are kept only if they match their checksums.
+async fn download_library(
+    artifact: ArtifactFile,
+    checksums: Vec<String>,
+    libraries_dir: &Path,
+    downloader: &Downloader,
+) -> Result<PathBuf, ForgeInstallError> {
+    let path = libraries_dir.join(&artifact.path);
+
+    if path.exists() {
+        if matches_checksums(&path, &checksums).await? {
+            return Ok(path);
+        }
+        debug!(
+            "{:?} matches none of its checksums, downloading it again",
+            path
+        );
+        std::fs::remove_file(&path)?;
+    }
+
+    let info = DownloadInfo {
+        path: path.clone(),
+        url: artifact.artifact.url,
+        size: artifact.artifact.size,
+        sha1: artifact.artifact.sha1,
+    };
+    match downloader.download(info, &|_: &Path, _| {}).await {
+        Ok(path) if matches_checksums(&path, &checksums).await? => return Ok(path),
+        Ok(path) => {
+            debug!("{:?} matches none of its checksums", path);
+            std::fs::remove_file(&path)?;
+        }
+        Err(e) => debug!("Failed to download {:?}: {:?}", path, e),
+    }
+
+    let info = DownloadInfo {
+        path,
+        url: format!("{}/{}", MAVEN_CENTRAL, artifact.path),
+        size: 0,
+        sha1: None,
+    };
+    let path = downloader.download(info, &|_: &Path, _| {}).await?;
+    if !matches_checksums(&path, &checksums).await? {
+        std::fs::remove_file(&path)?;
+        return Err(ForgeInstallError::InvalidChecksum(artifact.path));
+    }
+
+    Ok(path)
+}
+
+/// Whether a library matches one of its checksums, or has none.
+async fn matches_checksums(path: &Path, checksums: &[String]) -> Result<bool, ForgeInstallError> {
+    if checksums.is_empty() {
+        return Ok(true);
+    }
+
+    debug!("Checking library checksums: {:?}", path);
+    let path = path.to_path_buf();
+    let sha1 = tokio::task::spawn_blocking(move || file_sha1(&path)).await??;
+    Ok(checksums.contains(&sha1))
+}
+
+impl MinecraftVersion for LegacyForgeVersion {
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.vanilla.is_snapshot()
+    }
+
+    fn json_url(&self) -> &String {
+        &self.json_url
+    }
+
+    fn manifest(&self) -> &McVersionManifest {
+        &self.version
+    }
+
+    fn raw_json(&self) -> Option<&str> {
+        Some(&self.json)
+    }
+
+    fn parent(&self) -> Option<&dyn MinecraftVersion> {
+        Some(&self.vanilla)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_legacy_libraries() {
+        let install = LegacyInstall {
+            path: "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10".to_string(),
+            file_path: "forge-1.7.10-10.13.4.1614-1.7.10-universal.jar".to_string(),
+            minecraft: "1.7.10".to_string(),
+            target: "1.7.10-Forge10.13.4.1614-1.7.10".to_string(),
+        };
+        let mut version_info = serde_json::json!({
+            "libraries": [
+                { "name": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10", "url": "http://files.minecraftforge.net/maven/" },
+                { "name": "net.minecraft:launchwrapper:1.12", "serverreq": true },
+                { "name": "org.scala-lang:scala-library:2.11.1", "url": "http://files.minecraftforge.net/maven/", "clientreq": true, "serverreq": true },
+                { "name": "lzma:lzma:0.0.1" },
+                { "name": "com.mojang:realms:1.3.5", "clientreq": false }
+            ]
+        });
+
+        normalize_libraries(
+            &mut version_info,
+            &install,
+            "https://maven.minecraftforge.net",
+        );
+
+        assert_eq!(
+            version_info["libraries"],
+            serde_json::json!([
+                {
+                    "name": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10",
+                    "downloads": {
+                        "artifact": {
+                            "path": "net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10.jar",
+                            "url": ""
+                        }
+                    }
+                },
+                { "name": "net.minecraft:launchwrapper:1.12", "serverreq": true },
+                { "name": "org.scala-lang:scala-library:2.11.1", "url": "https://maven.minecraftforge.net/", "clientreq": true, "serverreq": true },
+                { "name": "lzma:lzma:0.0.1" }
+            ])
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Forge versions, installed with the processors of the Forge installers.
+
+pub mod install_profile;
+pub mod legacy;
+mod processors;
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use bauxite_download::{DownloadError, DownloadInfo, Downloader};
+use tempfile::TempDir;
+use tokio::task::JoinSet;
+use tracing::debug;
+use zip::ZipArchive;
+
+use crate::{
+    instance::Instance,
+    layout::Layout,
+    minecraft::{
+        jsons::{
+            partial_version_manifest::PartialVersionManifest, version_manifest::McVersionManifest,
+        },
+        maven::{MavenCoordinate, MavenCoordinateError},
+        vanilla::{VanillaVersion, VanillaVersionBuilder, VanillaVersionError},
+        version::MinecraftVersion,
+    },
+};
+
+use self::{install_profile::InstallProfile, processors::ProcessorContext};
+
+/// The name of the downloaded installers.
+const INSTALLER: &str = "installer.jar";
+
+/// A builder for creating a [`ForgeVersion`].
+pub struct ForgeVersionBuilder {
+    game_version: String,
+    forge_version: String,
+    downloader: Downloader,
+}
+
+impl ForgeVersionBuilder {
+    /// Create a new builder for the given game and Forge versions (e.g. `1.20.4` and `49.0.3`).
+    pub fn new(game_version: &str, forge_version: &str) -> Self {
+        debug!(
+            "Creating ForgeVersionBuilder with version: {}-{}",
+            game_version, forge_version
+        );
+        ForgeVersionBuilder {
+            game_version: game_version.to_string(),
+            forge_version: forge_version.to_string(),
+            downloader: Downloader::shared().clone(),
+        }
+    }
+
+    /// Set the downloader used to fetch the installer and the libraries (defaults to
+    /// [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Download the installer, and merge its version JSON into the vanilla version.
+    pub async fn build(self) -> Result<ForgeVersion, ForgeVersionError> {
+        debug!("Building ForgeVersion");
+        let coordinate = MavenCoordinate {
+            group: "net.minecraftforge".to_string(),
+            artifact: "forge".to_string(),
+            version: format!("{}-{}", self.game_version, self.forge_version),
+            classifier: Some("installer".to_string()),
+            extension: "jar".to_string(),
+        };
+        let maven = self.downloader.endpoints().forge_maven().to_string();
+
+        ForgeVersion::from_installer(
+            format!("Forge {} ({})", self.game_version, self.forge_version),
+            self.forge_version,
+            &coordinate,
+            &maven,
+            self.downloader,
+        )
+        .await
+    }
+}
+
+/// Download an installer from a maven repository into a temporary directory (as
+/// [`INSTALLER`]), returning the directory and the URL of the installer.
+///
+/// The installer is kept until the directory is dropped, the files it installs going into the
+/// layout of the instance.
+pub(crate) async fn download_installer(
+    coordinate: &MavenCoordinate,
+    maven: &str,
+    downloader: &Downloader,
+) -> Result<(TempDir, String), ForgeVersionError> {
+    let url = format!("{}/{}", maven, coordinate.path());
+    let installer_dir = tempfile::Builder::new()
+        .prefix("bauxite-forge-installer-")
+        .tempdir()?;
+    let installer = installer_dir.path().join(INSTALLER);
+
+    // The maven repositories publish the hash of each file next to it
+    let sha1 = match downloader.get_bytes(&format!("{}.sha1", url)).await {
+        Ok(sha1) => Some(String::from_utf8_lossy(&sha1).trim().to_string()),
+        Err(e) => {
+            debug!("No hash found for the installer: {:?}", e);
+            None
+        }
+    };
+
+    debug!("Downloading installer from {}", url);
+    downloader
+        .download(
+            DownloadInfo {
+                path: installer.clone(),
+                url: url.clone(),
+                size: 0,
+                sha1,
+            },
+            &|_: &Path, _| {},
+        )
+        .await?;
+
+    Ok((installer_dir, url))
+}
+
+/// Read the install profile and the version JSON of an installer.
+fn read_installer(installer: &Path) -> Result<(InstallProfile, String), ForgeVersionError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(installer)?))?;
+
+    let mut profile = String::new();
+    archive
+        .by_name("install_profile.json")?
+        .read_to_string(&mut profile)?;
+    let profile: InstallProfile = serde_json::from_str(&profile)?;
+
+    let mut json = String::new();
+    archive
+        .by_name(profile.json.trim_start_matches('/'))?
+        .read_to_string(&mut json)?;
+
+    Ok((profile, json))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ForgeVersionError {
+    #[error("Failed to download the Forge installer")]
+    DownloadError(#[from] DownloadError),
+    #[error("Failed to read the Forge installer")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to read the Forge installer")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Failed to parse the Forge installer profile")]
+    ParseError(#[from] serde_json::Error),
+    #[error("Failed to build the vanilla version")]
+    Vanilla(#[from] VanillaVersionError),
+    #[error("Failed to read the Forge installer")]
+    PoolError(#[from] tokio::task::JoinError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ForgeInstallError {
+    #[error("Failed to download the Forge libraries")]
+    DownloadError(#[from] DownloadError),
+    #[error("Failed to extract the Forge installer")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to extract the Forge installer")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Invalid library in the Forge installer")]
+    InvalidLibrary(#[from] MavenCoordinateError),
+    #[error("Missing value {0} in the Forge installer profile")]
+    MissingData(String),
+    #[error("No main class in the processor {0}")]
+    NoMainClass(String),
+    #[error("The processor {0} failed")]
+    ProcessorFailed(String),
+    #[error("The outputs of the processor {0} do not match their hash")]
+    InvalidOutput(String),
+    #[error("The instance does not run a Forge version")]
+    NotForge,
+    #[error("The library {0} matches none of its checksums")]
+    InvalidChecksum(String),
+    #[error("Failed to install Forge")]
+    PoolError(#[from] tokio::task::JoinError),
+}
+
+/// A vanilla version with Forge, or NeoForge which uses the same installers.
+///
+/// The patched client is generated by [`ForgeVersion::install`], which must be run on the
+/// instance before launching the game.
+#[derive(Debug)]
+pub struct ForgeVersion {
+    name: String,
+    id: String,
+    forge_version: String,
+    /// The Forge version JSON, merged into the vanilla version
+    version: McVersionManifest,
+    /// The Forge version JSON, as found in the installer
+    json: String,
+    /// The URL to the installer
+    json_url: String,
+    vanilla: VanillaVersion,
+    profile: InstallProfile,
+    /// The folder the installer is downloaded to
+    installer_dir: TempDir,
+    downloader: Downloader,
+}
+
+impl ForgeVersion {
+    /// Download an installer from a maven repository, and merge its version JSON into the
+    /// vanilla version.
+    pub(crate) async fn from_installer(
+        name: String,
+        forge_version: String,
+        coordinate: &MavenCoordinate,
+        maven: &str,
+        downloader: Downloader,
+    ) -> Result<ForgeVersion, ForgeVersionError> {
+        let (installer_dir, url) = download_installer(coordinate, maven, &downloader).await?;
+        let installer = installer_dir.path().join(INSTALLER);
+
+        let (profile, json) = {
+            let installer = installer.clone();
+            tokio::task::spawn_blocking(move || read_installer(&installer)).await??
+        };
+        let manifest: PartialVersionManifest = serde_json::from_str(&json)?;
+
+        let vanilla = VanillaVersionBuilder::new(&profile.minecraft)
+            .with_downloader(downloader.clone())
+            .build()
+            .await?;
+
+        Ok(ForgeVersion {
+            name,
+            id: manifest.id.clone(),
+            forge_version,
+            version: manifest.merge(vanilla.manifest()),
+            json,
+            json_url: url,
+            vanilla,
+            profile,
+            installer_dir,
+            downloader,
+        })
+    }
+
+    /// The version of Forge (or NeoForge).
+    pub fn forge_version(&self) -> &str {
+        &self.forge_version
+    }
+
+    /// The vanilla version Forge is installed on.
+    pub fn vanilla(&self) -> &VanillaVersion {
+        &self.vanilla
+    }
+
+    /// The install profile of the installer.
+    pub fn install_profile(&self) -> &InstallProfile {
+        &self.profile
+    }
+
+    /// Run the installer of the Forge version of an instance: download the vanilla client and
+    /// the libraries of the processors into the layout of the instance, then run the
+    /// processors with the given java executable.
+    ///
+    /// The processors whose outputs are up to date are skipped.
+    pub async fn install(
+        instance: &Instance,
+        java: impl AsRef<Path>,
+    ) -> Result<(), ForgeInstallError> {
+        let forge = instance
+            .version::<ForgeVersion>()
+            .ok_or(ForgeInstallError::NotForge)?;
+
+        forge.run_installer(instance.layout(), java.as_ref()).await
+    }
+
+    async fn run_installer(&self, layout: &Layout, java: &Path) -> Result<(), ForgeInstallError> {
+        debug!("Installing Forge {}", self.id);
+        let libraries_dir = layout.libraries_dir();
+        let installer = self.installer_dir.path().join(INSTALLER);
+
+        // The processors patch the vanilla client
+        let client = self.vanilla.manifest().downloads.client.clone();
+        let client_jar = layout.client_jar(self.vanilla.id());
+        self.downloader
+            .download(
+                DownloadInfo {
+                    path: client_jar.clone(),
+                    url: client.url,
+                    size: client.size,
+                    sha1: client.sha1,
+                },
+                &|_: &Path, _| {},
+            )
+            .await?;
+
+        self.download_libraries(layout).await?;
+
+        // The installer ships the Forge jars and the data files of the processors, the latter
+        // being removed once the processors are done
+        let data_dir = tempfile::Builder::new()
+            .prefix(&format!("bauxite-forge-{}-", self.id))
+            .tempdir()?;
+        {
+            let installer = installer.clone();
+            let libraries_dir = libraries_dir.clone();
+            let data_dir = data_dir.path().to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                extract_installer(&installer, &libraries_dir, &data_dir)
+            })
+            .await??;
+        }
+
+        let mut context = ProcessorContext {
+            data: HashMap::new(),
+            libraries_dir: libraries_dir.clone(),
+        };
+        for (key, value) in &self.profile.data {
+            let value = match &value.client {
+                value if value.starts_with('[') && value.ends_with(']') => {
+                    context.resolve(value)?
+                }
+                value if value.starts_with('\'') && value.ends_with('\'') => {
+                    value.trim_matches('\'').to_string()
+                }
+                value if value.starts_with('/') => data_dir
+                    .path()
+                    .join(value.trim_start_matches('/'))
+                    .to_string_lossy()
+                    .to_string(),
+                value => value.clone(),
+            };
+            context.data.insert(key.clone(), value);
+        }
+
+        let path = |path: &Path| path.to_string_lossy().to_string();
+        context.data.extend([
+            ("SIDE".to_string(), "client".to_string()),
+            ("MINECRAFT_JAR".to_string(), path(&client_jar)),
+            (
+                "MINECRAFT_VERSION".to_string(),
+                self.profile.minecraft.clone(),
+            ),
+            ("ROOT".to_string(), path(layout.root())),
+            ("INSTALLER".to_string(), path(&installer)),
+            ("LIBRARY_DIR".to_string(), path(&libraries_dir)),
+        ]);
+
+        for processor in &self.profile.processors {
+            if !processor.runs_on_client() {
+                continue;
+            }
+
+            context.run(processor, java).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Download the libraries of the install profile, the ones without URL being shipped in
+    /// the installer.
+    async fn download_libraries(&self, layout: &Layout) -> Result<(), ForgeInstallError> {
+        let mut joinset = JoinSet::new();
+
+        for library in &self.profile.libraries {
+            let Some(artifact) = library.artifact() else {
+                continue;
+            };
+            if artifact.artifact.url.is_empty() {
+                continue;
+            }
+
+            let info = DownloadInfo {
+                path: layout.libraries_dir().join(&artifact.path),
+                url: artifact.artifact.url,
+                size: artifact.artifact.size,
+                sha1: artifact.artifact.sha1,
+            };
+            let downloader = self.downloader.clone();
+            joinset.spawn(async move { downloader.download(info, &|_: &Path, _| {}).await });
+        }
+
+        while let Some(result) = joinset.join_next().await {
+            let path = result??;
+            debug!("Downloaded Forge library: {:?}", path);
+        }
+
+        Ok(())
+    }
+}
+
+/// Extract the maven repository of the installer into the libraries, and its data files.
+///
+/// The libraries already present are kept, unless their size differs from the installer ones.
+fn extract_installer(
+    installer: &Path,
+    libraries_dir: &Path,
+    data_dir: &Path,
+) -> Result<(), ForgeInstallError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(installer)?))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        if entry.is_dir() {
+            continue;
+        }
+
+        let output = match name.strip_prefix("maven") {
+            Ok(path) => {
+                let output = libraries_dir.join(path);
+                let size = std::fs::metadata(&output).map(|metadata| metadata.len());
+                if size.is_ok_and(|size| size == entry.size()) {
+                    continue;
+                }
+                output
+            }
+            Err(_) if name.starts_with("data") => data_dir.join(&name),
+            Err(_) => continue,
+        };
+
+        debug!("Extracting {} to {:?}", entry.name(), output);
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut File::create(&output)?)?;
+    }
+
+    Ok(())
+}
+
+impl MinecraftVersion for ForgeVersion {
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.vanilla.is_snapshot()
+    }
+
+    fn json_url(&self) -> &String {
+        &self.json_url
+    }
+
+    fn manifest(&self) -> &McVersionManifest {
+        &self.version
+    }
+
+    fn raw_json(&self) -> Option<&str> {
+        Some(&self.json)
+    }
+
+    fn parent(&self) -> Option<&dyn MinecraftVersion> {
+        Some(&self.vanilla)
+    }
+}
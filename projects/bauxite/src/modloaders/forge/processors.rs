@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use bauxite_download::file_sha1;
+use tokio::process::Command;
+use tracing::debug;
+use zip::ZipArchive;
+
+use crate::minecraft::{classpath::CLASSPATH_SEPARATOR, maven::MavenCoordinate};
+
+use super::{install_profile::Processor, ForgeInstallError};
+
+/// The values substituted in the processors arguments.
+#[derive(Debug, Clone)]
+pub(crate) struct ProcessorContext {
+    /// The `{KEY}` values, already resolved
+    pub data: HashMap<String, String>,
+    pub libraries_dir: PathBuf,
+}
+
+impl ProcessorContext {
+    /// The path of a library from its maven name.
+    pub fn library_path(&self, name: &str) -> Result<PathBuf, ForgeInstallError> {
+        let coordinate: MavenCoordinate = name.parse()?;
+        Ok(self.libraries_dir.join(coordinate.path()))
+    }
+
+    /// Resolve an argument: `{KEY}` is a data value, `[maven:name]` a library path, and
+    /// anything else is kept as is.
+    pub fn resolve(&self, arg: &str) -> Result<String, ForgeInstallError> {
+        if let Some(key) = arg.strip_prefix('{').and_then(|arg| arg.strip_suffix('}')) {
+            return self
+                .data
+                .get(key)
+                .cloned()
+                .ok_or_else(|| ForgeInstallError::MissingData(key.to_string()));
+        }
+
+        if let Some(name) = arg.strip_prefix('[').and_then(|arg| arg.strip_suffix(']')) {
+            return Ok(self.library_path(name)?.to_string_lossy().to_string());
+        }
+
+        Ok(arg.to_string())
+    }
+
+    /// Check that the outputs of a processor exist and have the expected SHA1.
+    fn outputs_match(&self, processor: &Processor) -> Result<bool, ForgeInstallError> {
+        for (path, sha1) in &processor.outputs {
+            let path = self.resolve(path)?;
+            let sha1 = self.resolve(sha1)?;
+
+            match file_sha1(&path) {
+                Ok(hash) if hash == sha1 => {}
+                Ok(_) => {
+                    debug!("Processor output {} does not match its hash", path);
+                    return Ok(false);
+                }
+                Err(_) => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Run a processor with the given java executable, unless its outputs are up to date.
+    pub async fn run(&self, processor: &Processor, java: &Path) -> Result<(), ForgeInstallError> {
+        if !processor.outputs.is_empty() && self.outputs_match(processor)? {
+            debug!("Processor {} is up to date, skipping it", processor.jar);
+            return Ok(());
+        }
+
+        let jar = self.library_path(&processor.jar)?;
+        let main_class = {
+            let jar = jar.clone();
+            tokio::task::spawn_blocking(move || main_class(&jar)).await??
+        };
+
+        let classpath = std::iter::once(Ok(jar))
+            .chain(
+                processor
+                    .classpath
+                    .iter()
+                    .map(|name| self.library_path(name)),
+            )
+            .map(|path| path.map(|path| path.to_string_lossy().to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let args = processor
+            .args
+            .iter()
+            .map(|arg| self.resolve(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        debug!(
+            "Running processor {} with arguments {:?}",
+            processor.jar, args
+        );
+        let output = Command::new(java)
+            .arg("-cp")
+            .arg(classpath.join(CLASSPATH_SEPARATOR))
+            .arg(main_class)
+            .args(&args)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            debug!(
+                "Processor {} failed: {}",
+                processor.jar,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return Err(ForgeInstallError::ProcessorFailed(processor.jar.clone()));
+        }
+
+        if !self.outputs_match(processor)? {
+            return Err(ForgeInstallError::InvalidOutput(processor.jar.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the `Main-Class` of an executable jar.
+fn main_class(jar: &Path) -> Result<String, ForgeInstallError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(jar)?))?;
+    let manifest = BufReader::new(archive.by_name("META-INF/MANIFEST.MF")?);
+
+    for line in manifest.lines() {
+        if let Some(main_class) = line?.strip_prefix("Main-Class:") {
+            return Ok(main_class.trim().to_string());
+        }
+    }
+
+    Err(ForgeInstallError::NoMainClass(
+        jar.to_string_lossy().to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_arguments() {
+        let context = ProcessorContext {
+            data: HashMap::from([("SIDE".to_string(), "client".to_string())]),
+            libraries_dir: PathBuf::from("/libraries"),
+        };
+
+        assert_eq!(context.resolve("{SIDE}").unwrap(), "client");
+        assert_eq!(
+            PathBuf::from(
+                context
+                    .resolve("[de.oceanlabs.mcp:mcp_config:1.20.4@zip]")
+                    .unwrap()
+            ),
+            PathBuf::from("/libraries/de/oceanlabs/mcp/mcp_config/1.20.4/mcp_config-1.20.4.zip")
+        );
+        assert_eq!(context.resolve("--task").unwrap(), "--task");
+        assert!(matches!(
+            context.resolve("{MISSING}"),
+            Err(ForgeInstallError::MissingData(key)) if key == "MISSING"
+        ));
+    }
+}
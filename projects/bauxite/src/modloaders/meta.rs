@@ -0,0 +1,237 @@
+//! The meta APIs shared by Fabric and its forks, listing the loader versions and serving their
+//! launcher profiles.
+
+use std::fmt::Debug;
+
+use bauxite_download::{Downloader, Endpoints, FetchError};
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::minecraft::{
+    jsons::{
+        partial_version_manifest::PartialVersionManifest, version_manifest::McVersionManifest,
+    },
+    vanilla::{VanillaVersion, VanillaVersionBuilder, VanillaVersionError},
+    version::MinecraftVersion,
+};
+
+/// A loader served by a meta API, such as [`Fabric`](super::fabric::Fabric) or
+/// [`Quilt`](super::quilt::Quilt).
+pub trait MetaLoader: 'static {
+    /// The name of the loader, used in the version names.
+    const NAME: &'static str;
+
+    /// A loader version, as listed by the meta API.
+    type LoaderVersion: DeserializeOwned + Debug + Clone;
+
+    /// The URL of the meta API.
+    fn meta_url(endpoints: &Endpoints) -> &str;
+
+    /// The version of the loader itself.
+    fn loader_version(version: &Self::LoaderVersion) -> &str;
+
+    /// Check if a loader version is picked when no version is requested.
+    fn is_stable(version: &Self::LoaderVersion) -> bool;
+}
+
+/// The launcher profile of a loader version, inheriting from the vanilla version.
+pub(crate) struct LoaderProfile {
+    pub manifest: PartialVersionManifest,
+    /// The profile, as downloaded
+    pub json: String,
+    pub url: String,
+}
+
+/// List the loader versions compatible with a game version, newest first.
+pub(crate) async fn fetch_loader_versions<T: DeserializeOwned>(
+    downloader: &Downloader,
+    meta_url: &str,
+    game_version: &str,
+) -> Result<Vec<T>, FetchError> {
+    let url = format!("{}/versions/loader/{}", meta_url, game_version);
+    debug!("Fetching loader versions from {}", url);
+
+    downloader.get_json(&url).await
+}
+
+/// Fetch the launcher profile of a loader version.
+pub(crate) async fn fetch_profile(
+    downloader: &Downloader,
+    meta_url: &str,
+    game_version: &str,
+    loader_version: &str,
+) -> Result<LoaderProfile, MetaVersionError> {
+    let url = format!(
+        "{}/versions/loader/{}/{}/profile/json",
+        meta_url, game_version, loader_version
+    );
+    debug!("Fetching loader profile from {}", url);
+
+    let json = downloader.get_bytes(&url).await?;
+    let json = String::from_utf8(json).map_err(MetaVersionError::InvalidEncoding)?;
+    let manifest = serde_json::from_str(&json).map_err(FetchError::from)?;
+
+    Ok(LoaderProfile {
+        manifest,
+        json,
+        url,
+    })
+}
+
+/// A builder for creating a [`MetaVersion`].
+pub struct MetaVersionBuilder<L: MetaLoader> {
+    game_version: String,
+    loader_version: Option<String>,
+    downloader: Downloader,
+    loader: std::marker::PhantomData<L>,
+}
+
+impl<L: MetaLoader> MetaVersionBuilder<L> {
+    /// Create a new builder for the given game version, with the latest stable loader.
+    pub fn new(game_version: &str) -> Self {
+        debug!(
+            "Creating {} version builder with version: {}",
+            L::NAME,
+            game_version
+        );
+        MetaVersionBuilder {
+            game_version: game_version.to_string(),
+            loader_version: None,
+            downloader: Downloader::shared().clone(),
+            loader: std::marker::PhantomData,
+        }
+    }
+
+    /// Use the given loader version instead of the latest stable one.
+    pub fn with_loader_version(mut self, loader_version: &str) -> Self {
+        self.loader_version = Some(loader_version.to_string());
+        self
+    }
+
+    /// Set the downloader used to fetch the version JSONs (defaults to [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Build the [`MetaVersion`], merging the loader profile into the vanilla version.
+    pub async fn build(self) -> Result<MetaVersion<L>, MetaVersionError> {
+        debug!("Building {} version", L::NAME);
+        let meta_url = L::meta_url(self.downloader.endpoints());
+        let loaders: Vec<L::LoaderVersion> =
+            fetch_loader_versions(&self.downloader, meta_url, &self.game_version).await?;
+
+        let loader = match &self.loader_version {
+            Some(version) => loaders.iter().find(|l| L::loader_version(l) == version),
+            None => loaders
+                .iter()
+                .find(|l| L::is_stable(l))
+                .or_else(|| loaders.first()),
+        };
+        let Some(loader) = loader else {
+            return Err(MetaVersionError::LoaderVersionNotFound);
+        };
+        let loader_version = L::loader_version(loader);
+
+        let vanilla = VanillaVersionBuilder::new(&self.game_version)
+            .with_downloader(self.downloader.clone())
+            .build()
+            .await?;
+
+        let LoaderProfile {
+            manifest,
+            json,
+            url,
+        } = fetch_profile(
+            &self.downloader,
+            meta_url,
+            &self.game_version,
+            loader_version,
+        )
+        .await?;
+
+        Ok(MetaVersion {
+            name: format!("{} {} ({})", L::NAME, self.game_version, loader_version),
+            id: manifest.id.clone(),
+            loader: loader.clone(),
+            version: manifest.merge(vanilla.manifest()),
+            json,
+            json_url: url,
+            vanilla,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MetaVersionError {
+    #[error("Loader version not found")]
+    LoaderVersionNotFound,
+    #[error("Failed to fetch the loader meta")]
+    FetchMeta(#[from] FetchError),
+    #[error("The loader profile is not valid UTF-8")]
+    InvalidEncoding(#[source] std::string::FromUtf8Error),
+    #[error("Failed to build the vanilla version")]
+    Vanilla(#[from] VanillaVersionError),
+}
+
+/// A vanilla version with a loader served by a meta API.
+#[derive(Debug)]
+pub struct MetaVersion<L: MetaLoader> {
+    name: String,
+    id: String,
+    loader: L::LoaderVersion,
+    /// The loader profile, merged into the vanilla version
+    version: McVersionManifest,
+    /// The loader profile, as downloaded
+    json: String,
+    /// The URL to the loader profile
+    json_url: String,
+    vanilla: VanillaVersion,
+}
+
+impl<L: MetaLoader> MetaVersion<L> {
+    /// The version of the loader.
+    pub fn loader_version(&self) -> &str {
+        L::loader_version(&self.loader)
+    }
+
+    /// The loader and the mappings used by this version.
+    pub fn loader(&self) -> &L::LoaderVersion {
+        &self.loader
+    }
+
+    /// The vanilla version the loader is installed on.
+    pub fn vanilla(&self) -> &VanillaVersion {
+        &self.vanilla
+    }
+}
+
+impl<L: MetaLoader> MinecraftVersion for MetaVersion<L> {
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.vanilla.is_snapshot()
+    }
+
+    fn json_url(&self) -> &String {
+        &self.json_url
+    }
+
+    fn manifest(&self) -> &McVersionManifest {
+        &self.version
+    }
+
+    fn raw_json(&self) -> Option<&str> {
+        Some(&self.json)
+    }
+
+    fn parent(&self) -> Option<&dyn MinecraftVersion> {
+        Some(&self.vanilla)
+    }
+}
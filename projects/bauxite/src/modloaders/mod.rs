@@ -0,0 +1,5 @@
+pub mod fabric;
+pub mod forge;
+pub mod meta;
+pub mod neoforge;
+pub mod quilt;
@@ -0,0 +1,207 @@
+//! NeoForge versions, installed with the same installers as Forge.
+
+use std::sync::OnceLock;
+
+use bauxite_download::{Downloader, FetchError};
+use regex::Regex;
+use tracing::debug;
+
+use crate::minecraft::maven::MavenCoordinate;
+
+use super::forge::{ForgeVersion, ForgeVersionError};
+
+/// The game version whose NeoForge builds are published like the Forge ones, as
+/// `net.neoforged:forge:1.20.1-<version>`.
+const LEGACY_GAME_VERSION: &str = "1.20.1";
+
+/// List the NeoForge versions, oldest first.
+///
+/// The builds for 1.20.1 are not listed, see [`NeoForgeVersionBuilder`].
+pub async fn fetch_neoforge_versions(downloader: &Downloader) -> Result<Vec<String>, FetchError> {
+    fetch_versions(downloader, "neoforge").await
+}
+
+/// List the versions of a `net.neoforged` artifact, as published in its maven metadata.
+async fn fetch_versions(
+    downloader: &Downloader,
+    artifact: &str,
+) -> Result<Vec<String>, FetchError> {
+    let url = format!(
+        "{}/net/neoforged/{}/maven-metadata.xml",
+        downloader.endpoints().neoforge_maven(),
+        artifact
+    );
+    debug!("Fetching NeoForge versions from {}", url);
+
+    let metadata = downloader.get_bytes(&url).await?;
+    let metadata = String::from_utf8_lossy(&metadata);
+
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r"<version>([^<]+)</version>").unwrap());
+
+    Ok(version
+        .captures_iter(&metadata)
+        .map(|captures| captures[1].trim().to_string())
+        .collect())
+}
+
+/// The game version of a NeoForge version: its major and minor numbers are the minor and
+/// patch numbers of the game (`20.4.237` is for `1.20.4`, `21.0.10-beta` for `1.21`).
+pub fn game_version(neoforge_version: &str) -> Option<String> {
+    let mut parts = neoforge_version.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+
+    match minor {
+        0 => Some(format!("1.{}", major)),
+        minor => Some(format!("1.{}.{}", major, minor)),
+    }
+}
+
+/// The latest version of a list, comparing their numbers rather than their text (`20.4.80`
+/// is older than `20.4.237`), and preferring the stable ones.
+fn latest_version<'a>(versions: &[&'a String]) -> Option<&'a String> {
+    // The pre-releases have a suffix (`21.0.10-beta`)
+    let key = |version: &&String| {
+        let (numbers, suffix) = match version.split_once('-') {
+            Some((numbers, _)) => (numbers, false),
+            None => (version.as_str(), true),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|number| number.parse::<u32>().unwrap_or_default())
+            .collect::<Vec<_>>();
+        (suffix, numbers)
+    };
+
+    versions.iter().copied().max_by_key(key)
+}
+
+/// A builder for creating a NeoForge [`ForgeVersion`].
+///
+/// The NeoForge builds for 1.20.1 are published as `net.neoforged:forge`, with the Forge
+/// version numbers (`47.1.106`), and are picked with these numbers as well.
+pub struct NeoForgeVersionBuilder {
+    game_version: String,
+    neoforge_version: Option<String>,
+    downloader: Downloader,
+}
+
+impl NeoForgeVersionBuilder {
+    /// Create a new builder for the given game version, with the latest stable NeoForge.
+    pub fn new(game_version: &str) -> Self {
+        debug!(
+            "Creating NeoForgeVersionBuilder with version: {}",
+            game_version
+        );
+        NeoForgeVersionBuilder {
+            game_version: game_version.to_string(),
+            neoforge_version: None,
+            downloader: Downloader::shared().clone(),
+        }
+    }
+
+    /// Use the given NeoForge version instead of the latest stable one.
+    pub fn with_neoforge_version(mut self, neoforge_version: &str) -> Self {
+        self.neoforge_version = Some(neoforge_version.to_string());
+        self
+    }
+
+    /// Set the downloader used to fetch the installer and the libraries (defaults to
+    /// [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Download the installer, and merge its version JSON into the vanilla version.
+    ///
+    /// The returned version must be installed with [`ForgeVersion::install`].
+    pub async fn build(self) -> Result<ForgeVersion, NeoForgeVersionError> {
+        debug!("Building NeoForge version");
+        let legacy = self.game_version == LEGACY_GAME_VERSION;
+        let versions: Vec<String> = if legacy {
+            let prefix = format!("{}-", LEGACY_GAME_VERSION);
+            fetch_versions(&self.downloader, "forge")
+                .await?
+                .into_iter()
+                .filter_map(|version| version.strip_prefix(&prefix).map(str::to_string))
+                .collect()
+        } else {
+            fetch_neoforge_versions(&self.downloader)
+                .await?
+                .into_iter()
+                .filter(|version| game_version(version).as_deref() == Some(&self.game_version))
+                .collect()
+        };
+        let compatible: Vec<&String> = versions.iter().collect();
+
+        let version = match &self.neoforge_version {
+            Some(neoforge_version) => compatible
+                .into_iter()
+                .find(|version| *version == neoforge_version),
+            None => latest_version(&compatible),
+        };
+        let Some(version) = version else {
+            return Err(NeoForgeVersionError::VersionNotFound);
+        };
+
+        let (artifact, artifact_version) = match legacy {
+            true => ("forge", format!("{}-{}", LEGACY_GAME_VERSION, version)),
+            false => ("neoforge", version.clone()),
+        };
+        let coordinate = MavenCoordinate {
+            group: "net.neoforged".to_string(),
+            artifact: artifact.to_string(),
+            version: artifact_version,
+            classifier: Some("installer".to_string()),
+            extension: "jar".to_string(),
+        };
+        let maven = self.downloader.endpoints().neoforge_maven().to_string();
+
+        Ok(ForgeVersion::from_installer(
+            format!("NeoForge {} ({})", self.game_version, version),
+            version.clone(),
+            &coordinate,
+            &maven,
+            self.downloader,
+        )
+        .await?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NeoForgeVersionError {
+    #[error("NeoForge version not found")]
+    VersionNotFound,
+    #[error("Failed to fetch the NeoForge versions")]
+    FetchVersions(#[from] FetchError),
+    #[error("Failed to build the NeoForge version")]
+    Installer(#[from] ForgeVersionError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_game_versions() {
+        assert_eq!(game_version("20.4.237").as_deref(), Some("1.20.4"));
+        assert_eq!(game_version("20.2.3-beta").as_deref(), Some("1.20.2"));
+        assert_eq!(game_version("21.0.10-beta").as_deref(), Some("1.21"));
+        assert_eq!(game_version("invalid"), None);
+    }
+
+    #[test]
+    fn pick_latest_version() {
+        let versions = ["20.4.80", "20.4.237", "20.4.238-beta", "20.4.9"].map(String::from);
+        let versions = versions.iter().collect::<Vec<_>>();
+        assert_eq!(latest_version(&versions).unwrap(), "20.4.237");
+
+        let versions = ["21.0.9-beta", "21.0.10-beta"].map(String::from);
+        let versions = versions.iter().collect::<Vec<_>>();
+        assert_eq!(latest_version(&versions).unwrap(), "21.0.10-beta");
+
+        assert_eq!(latest_version(&[]), None);
+    }
+}
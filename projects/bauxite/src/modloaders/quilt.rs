@@ -0,0 +1,71 @@
+use bauxite_download::{Downloader, Endpoints, FetchError};
+use serde::Deserialize;
+
+use super::meta::{MetaLoader, MetaVersion, MetaVersionBuilder, MetaVersionError};
+
+/// The Quilt loader, served by the Quilt meta API.
+#[derive(Debug)]
+pub struct Quilt;
+
+impl MetaLoader for Quilt {
+    const NAME: &'static str = "Quilt";
+
+    type LoaderVersion = QuiltLoaderVersion;
+
+    fn meta_url(endpoints: &Endpoints) -> &str {
+        endpoints.quilt_meta()
+    }
+
+    fn loader_version(version: &QuiltLoaderVersion) -> &str {
+        &version.loader.version
+    }
+
+    fn is_stable(version: &QuiltLoaderVersion) -> bool {
+        version.loader.is_stable()
+    }
+}
+
+/// A builder for creating a [`QuiltVersion`].
+pub type QuiltVersionBuilder = MetaVersionBuilder<Quilt>;
+/// A vanilla version with the Quilt loader.
+pub type QuiltVersion = MetaVersion<Quilt>;
+pub type QuiltVersionError = MetaVersionError;
+
+/// A Quilt loader version, and the mappings of the game version it was listed for.
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuiltLoaderVersion {
+    pub loader: QuiltComponent,
+    /// The hashed mappings, used by Quilt at runtime
+    pub hashed: QuiltComponent,
+    /// The Fabric intermediary mappings, used by the Fabric mods
+    pub intermediary: QuiltComponent,
+}
+
+/// A component of the Quilt meta API (loader, mappings...).
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuiltComponent {
+    /// The maven name of the component
+    pub maven: String,
+    pub version: String,
+}
+
+impl QuiltComponent {
+    /// Quilt does not flag its stable versions, only its pre-releases have a suffix
+    /// (`0.26.0-beta.1`).
+    pub fn is_stable(&self) -> bool {
+        !self.version.contains('-')
+    }
+}
+
+/// List the Quilt loader versions compatible with a game version, newest first.
+pub async fn fetch_loader_versions(
+    downloader: &Downloader,
+    game_version: &str,
+) -> Result<Vec<QuiltLoaderVersion>, FetchError> {
+    super::meta::fetch_loader_versions(
+        downloader,
+        downloader.endpoints().quilt_meta(),
+        game_version,
+    )
+    .await
+}
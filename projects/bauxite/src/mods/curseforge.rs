@@ -0,0 +1,447 @@
+//! A client of the [CurseForge API](https://docs.curseforge.com/rest-api/).
+
+use std::path::{Path, PathBuf};
+
+use bauxite_download::{DownloadError, DownloadInfo, Downloader};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+use crate::Instance;
+
+/// The number of files requested per page.
+const PAGE_SIZE: u32 = 50;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CurseForgeError {
+    #[error("Failed to query the CurseForge API")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Failed to parse the CurseForge API response")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("The author of the mod {mod_id} does not allow the download of the file {file_id} by third-party launchers")]
+    DownloadDisallowed { mod_id: u32, file_id: u32 },
+
+    #[error("Invalid file name: {0}")]
+    InvalidFileName(String),
+
+    #[error("Failed to download the file")]
+    DownloadError(#[from] DownloadError),
+
+    #[error("Failed to join the download task")]
+    PoolError(#[from] tokio::task::JoinError),
+}
+
+/// The mod loaders, as filtered by the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModLoaderType {
+    Any = 0,
+    Forge = 1,
+    Cauldron = 2,
+    LiteLoader = 3,
+    Fabric = 4,
+    Quilt = 5,
+    NeoForge = 6,
+}
+
+/// The response of the API, wrapping the requested data.
+#[derive(Deserialize, Debug)]
+struct Response<T> {
+    data: T,
+    pagination: Option<Pagination>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Pagination {
+    index: u32,
+    result_count: u32,
+    total_count: u32,
+}
+
+/// A mod (or any other project) of CurseForge.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    pub id: u32,
+    pub name: String,
+    pub slug: String,
+    pub summary: String,
+    pub links: CurseForgeLinks,
+    /// `Some(false)` if the files can't be downloaded by third-party launchers
+    pub allow_mod_distribution: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeLinks {
+    pub website_url: String,
+}
+
+/// A file of a mod.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+    pub id: u32,
+    pub mod_id: u32,
+    pub display_name: String,
+    pub file_name: String,
+    pub file_length: u64,
+    /// Missing if the author does not allow the download by third-party launchers
+    pub download_url: Option<String>,
+    #[serde(default)]
+    pub hashes: Vec<FileHash>,
+    /// The [`fingerprint`] of the file
+    pub file_fingerprint: u32,
+    /// The game versions, the loaders and the environments of the file
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<FileDependency>,
+}
+
+impl CurseForgeFile {
+    /// The SHA1 of the file, if the API provides it.
+    pub fn sha1(&self) -> Option<&str> {
+        self.hashes
+            .iter()
+            .find(|hash| hash.algo == HashAlgo::Sha1)
+            .map(|hash| hash.value.as_str())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FileHash {
+    pub value: String,
+    pub algo: HashAlgo,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8")]
+pub enum HashAlgo {
+    Sha1,
+    Md5,
+    Unknown(u8),
+}
+
+impl From<u8> for HashAlgo {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => HashAlgo::Sha1,
+            2 => HashAlgo::Md5,
+            value => HashAlgo::Unknown(value),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDependency {
+    pub mod_id: u32,
+    pub relation_type: RelationType,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8")]
+pub enum RelationType {
+    EmbeddedLibrary,
+    OptionalDependency,
+    RequiredDependency,
+    Tool,
+    Incompatible,
+    Include,
+    Unknown(u8),
+}
+
+impl From<u8> for RelationType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => RelationType::EmbeddedLibrary,
+            2 => RelationType::OptionalDependency,
+            3 => RelationType::RequiredDependency,
+            4 => RelationType::Tool,
+            5 => RelationType::Incompatible,
+            6 => RelationType::Include,
+            value => RelationType::Unknown(value),
+        }
+    }
+}
+
+/// The result of [`CurseForgeClient::download_files`].
+#[derive(Debug, Default)]
+pub struct DownloadReport {
+    /// The paths of the downloaded files
+    pub downloaded: Vec<PathBuf>,
+    /// The files which must be downloaded manually from the CurseForge website, their
+    /// authors not allowing third-party launchers to download them
+    pub disallowed: Vec<CurseForgeFile>,
+    /// The files which failed to download, and their error
+    pub failed: Vec<(CurseForgeFile, CurseForgeError)>,
+}
+
+/// A client of the CurseForge API, authenticated with an API key.
+#[derive(Debug, Clone)]
+pub struct CurseForgeClient {
+    downloader: Downloader,
+    api_key: String,
+}
+
+impl CurseForgeClient {
+    /// Create a client using the given API key.
+    pub fn new(api_key: &str) -> Self {
+        CurseForgeClient {
+            downloader: Downloader::shared().clone(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    /// Set the downloader used for the requests and the files (defaults to
+    /// [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Get a mod by ID.
+    pub async fn get_mod(&self, mod_id: u32) -> Result<CurseForgeMod, CurseForgeError> {
+        Ok(self.get(&format!("/mods/{}", mod_id), &[]).await?.data)
+    }
+
+    /// Get a file of a mod by ID.
+    pub async fn get_file(
+        &self,
+        mod_id: u32,
+        file_id: u32,
+    ) -> Result<CurseForgeFile, CurseForgeError> {
+        Ok(self
+            .get(&format!("/mods/{}/files/{}", mod_id, file_id), &[])
+            .await?
+            .data)
+    }
+
+    /// List the files of a mod, newest first, only keeping the ones for the given game
+    /// version and loader.
+    pub async fn mod_files(
+        &self,
+        mod_id: u32,
+        game_version: Option<&str>,
+        loader: Option<ModLoaderType>,
+    ) -> Result<Vec<CurseForgeFile>, CurseForgeError> {
+        let mut query = vec![("pageSize", PAGE_SIZE.to_string())];
+        if let Some(game_version) = game_version {
+            query.push(("gameVersion", game_version.to_string()));
+        }
+        if let Some(loader) = loader {
+            query.push(("modLoaderType", (loader as u8).to_string()));
+        }
+
+        let mut files = Vec::new();
+        let mut index = 0;
+        loop {
+            let mut page_query = query.clone();
+            page_query.push(("index", index.to_string()));
+
+            let response: Response<Vec<CurseForgeFile>> = self
+                .get(&format!("/mods/{}/files", mod_id), &page_query)
+                .await?;
+            files.extend(response.data);
+
+            match response.pagination {
+                Some(page)
+                    if page.result_count > 0
+                        && page.index + page.result_count < page.total_count =>
+                {
+                    index = page.index + page.result_count;
+                }
+                _ => return Ok(files),
+            }
+        }
+    }
+
+    /// Download files into the mods folder of an instance.
+    ///
+    /// The files which can't be downloaded by third-party launchers, or which failed to
+    /// download, are reported instead of failing the whole download.
+    pub async fn download_files(
+        &self,
+        files: &[CurseForgeFile],
+        instance: &Instance,
+    ) -> Result<DownloadReport, CurseForgeError> {
+        let mut report = DownloadReport::default();
+        let mut joinset = JoinSet::new();
+
+        for file in files {
+            if file.download_url.is_none() {
+                debug!("The download of {} is disallowed", file.file_name);
+                report.disallowed.push(file.clone());
+                continue;
+            }
+
+            let client = self.clone();
+            let file = file.clone();
+            let dir = instance.mods_dir();
+            joinset.spawn(async move {
+                let result = client.download_file(&file, &dir).await;
+                (file, result)
+            });
+        }
+
+        while let Some(result) = joinset.join_next().await {
+            match result {
+                Ok((_, Ok(path))) => report.downloaded.push(path),
+                Ok((file, Err(e))) => {
+                    debug!("Failed to download {}: {:?}", file.file_name, e);
+                    report.failed.push((file, e));
+                }
+                // The file of a panicked task is lost, the other downloads are still reported
+                Err(e) => warn!("A download task failed: {:?}", e),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Download a file into a folder, checking its SHA1 and its fingerprint.
+    pub async fn download_file(
+        &self,
+        file: &CurseForgeFile,
+        dir: &Path,
+    ) -> Result<PathBuf, CurseForgeError> {
+        let Some(url) = &file.download_url else {
+            return Err(CurseForgeError::DownloadDisallowed {
+                mod_id: file.mod_id,
+                file_id: file.id,
+            });
+        };
+        let name = Path::new(&file.file_name)
+            .file_name()
+            .filter(|name| *name == file.file_name.as_str())
+            .ok_or_else(|| CurseForgeError::InvalidFileName(file.file_name.clone()))?;
+        let path = dir.join(name);
+
+        if path.exists()
+            && check_fingerprint(&path, file.file_fingerprint)
+                .await
+                .is_err()
+        {
+            debug!("Replacing the outdated file {:?}", path);
+            std::fs::remove_file(&path).map_err(DownloadError::from)?;
+        }
+
+        let info = DownloadInfo {
+            path: path.clone(),
+            url: url.clone(),
+            size: file.file_length,
+            sha1: file.sha1().map(str::to_string),
+        };
+        self.downloader.download(info, &|_: &Path, _| {}).await?;
+
+        if let Err(e) = check_fingerprint(&path, file.file_fingerprint).await {
+            let _ = std::fs::remove_file(&path);
+            return Err(e);
+        }
+
+        Ok(path)
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Response<T>, CurseForgeError> {
+        let endpoints = self.downloader.endpoints();
+        let url = endpoints.rewrite(&format!("{}{}", endpoints.curseforge_api(), path));
+
+        debug!("Querying {}", url);
+        let body = self
+            .downloader
+            .client()
+            .get(&url)
+            .query(query)
+            .header("x-api-key", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// The fingerprint of a file used by CurseForge: the MurmurHash2 (seed 1) of its content,
+/// without the whitespace bytes.
+pub fn fingerprint(content: &[u8]) -> u32 {
+    let content = content
+        .iter()
+        .copied()
+        .filter(|byte| !matches!(byte, b'\t' | b'\n' | b'\r' | b' '))
+        .collect::<Vec<_>>();
+
+    murmur2(&content, 1)
+}
+
+async fn check_fingerprint(path: &Path, expected: u32) -> Result<(), CurseForgeError> {
+    debug!("Checking file fingerprint: {:?}", path);
+    let path = path.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || {
+        std::fs::read(path).map(|content| fingerprint(&content))
+    })
+    .await?
+    .map_err(DownloadError::from)?;
+    if actual == expected {
+        return Ok(());
+    }
+
+    Err(DownloadError::InvalidChecksum.into())
+}
+
+fn murmur2(key: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+
+    let mut h = seed ^ key.len() as u32;
+
+    let mut chunks = key.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        for (i, byte) in remainder.iter().enumerate() {
+            h ^= u32::from(*byte) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur2_reference() {
+        let seed = 3_242_157_231;
+        assert_eq!(murmur2(b"", seed), 3_632_506_080);
+        assert_eq!(murmur2(b"a", seed), 455_683_869);
+        assert_eq!(murmur2(b"ab", seed), 2_448_092_234);
+        assert_eq!(murmur2(b"abc", seed), 2_066_295_634);
+        assert_eq!(murmur2(b"abcd", seed), 2_588_571_162);
+        assert_eq!(murmur2(b"abcdefghijklmnop", seed), 2_350_868_870);
+    }
+
+    #[test]
+    fn fingerprint_ignores_whitespace() {
+        assert_eq!(fingerprint(b"a b\tc\r\nd"), fingerprint(b"abcd"));
+        assert_eq!(fingerprint(b"abcd"), murmur2(b"abcd", 1));
+    }
+}
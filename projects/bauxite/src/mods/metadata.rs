@@ -0,0 +1,764 @@
+//! Read the metadata of the installed mods from their jars.
+//!
+//! The supported metadata files are `fabric.mod.json`, `quilt.mod.json`,
+//! `META-INF/neoforge.mods.toml`, `META-INF/mods.toml` and the legacy Forge `mcmod.info`.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use tracing::debug;
+use zip::{result::ZipError, ZipArchive};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ModMetadataError {
+    #[error("Failed to read the mod")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to open the mod jar")]
+    ZipError(#[from] ZipError),
+
+    #[error("Failed to parse {0}")]
+    ParseJson(String, #[source] serde_json::Error),
+
+    #[error("Failed to parse {0}")]
+    ParseToml(String, #[source] toml::de::Error),
+}
+
+/// The loader a mod metadata is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModLoader {
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    Required,
+    Optional,
+    Incompatible,
+}
+
+/// A dependency declared by a mod.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModDependency {
+    pub id: String,
+    /// The accepted versions in the syntax of the loader (semver ranges for Fabric and Quilt,
+    /// maven ranges for Forge and NeoForge), `*` for any version
+    pub version_range: String,
+    pub kind: DependencyKind,
+}
+
+/// A mod found in a jar.
+#[derive(Debug, Clone)]
+pub struct ModInfo {
+    /// The path to the jar
+    pub path: PathBuf,
+    pub id: String,
+    /// The display name of the mod, or its ID if it has none
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub loader: ModLoader,
+    pub dependencies: Vec<ModDependency>,
+    /// The path to the icon in the jar
+    pub icon: Option<String>,
+}
+
+impl ModInfo {
+    /// Read the icon of the mod from its jar.
+    pub fn read_icon(&self) -> Result<Option<Vec<u8>>, ModMetadataError> {
+        let Some(icon) = &self.icon else {
+            return Ok(None);
+        };
+        let mut archive = ZipArchive::new(BufReader::new(File::open(&self.path)?))?;
+        read_entry(&mut archive, icon)
+    }
+}
+
+/// The mods of a folder, and the jars whose metadata could not be read.
+#[derive(Debug, Default)]
+pub struct ModList {
+    pub mods: Vec<ModInfo>,
+    pub errors: Vec<(PathBuf, ModMetadataError)>,
+}
+
+/// List the mods of a folder, like [`Instance::mods_dir`](crate::Instance::mods_dir).
+///
+/// The jars without any metadata (e.g. libraries) are ignored.
+pub fn list_mods(mods_dir: &Path) -> Result<ModList, ModMetadataError> {
+    let mut list = ModList::default();
+
+    let entries = match std::fs::read_dir(mods_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(list),
+        Err(e) => return Err(e.into()),
+    };
+    let mut jars = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    jars.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "jar"));
+    jars.sort();
+
+    for jar in jars {
+        match read_mod_jar(&jar) {
+            Ok(mods) => list.mods.extend(mods),
+            Err(e) => {
+                debug!("Failed to read the metadata of {:?}: {:?}", jar, e);
+                list.errors.push((jar, e));
+            }
+        }
+    }
+
+    Ok(list)
+}
+
+/// Read the mods declared by the metadata files of a jar.
+///
+/// A jar built for several loaders has a [`ModInfo`] for each of them.
+pub fn read_mod_jar(path: &Path) -> Result<Vec<ModInfo>, ModMetadataError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut mods = Vec::new();
+
+    if let Some(json) = read_entry(&mut archive, "quilt.mod.json")? {
+        mods.push(parse_json::<QuiltModJson>("quilt.mod.json", &json)?.into_info());
+    }
+    if let Some(json) = read_entry(&mut archive, "fabric.mod.json")? {
+        mods.push(parse_json::<FabricModJson>("fabric.mod.json", &json)?.into_info());
+    }
+    for (name, loader) in [
+        ("META-INF/neoforge.mods.toml", ModLoader::NeoForge),
+        ("META-INF/mods.toml", ModLoader::Forge),
+    ] {
+        if let Some(toml) = read_entry(&mut archive, name)? {
+            let toml = String::from_utf8_lossy(&toml);
+            let mods_toml: ModsToml =
+                toml::from_str(&toml).map_err(|e| ModMetadataError::ParseToml(name.into(), e))?;
+            let jar_version = manifest_attribute(&mut archive, "Implementation-Version")?;
+            mods.extend(mods_toml.into_infos(loader, jar_version.as_deref()));
+        }
+    }
+    if let Some(json) = read_entry(&mut archive, "mcmod.info")? {
+        let info = parse_json::<McModInfo>("mcmod.info", &json)?;
+        mods.extend(info.into_infos());
+    }
+
+    for info in &mut mods {
+        info.path = path.to_path_buf();
+    }
+
+    Ok(mods)
+}
+
+fn read_entry<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, ModMetadataError> {
+    let mut entry = match archive.by_name(name.trim_start_matches('/')) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut content = Vec::new();
+    entry.read_to_end(&mut content)?;
+    Ok(Some(content))
+}
+
+fn manifest_attribute<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    attribute: &str,
+) -> Result<Option<String>, ModMetadataError> {
+    let Some(manifest) = read_entry(archive, "META-INF/MANIFEST.MF")? else {
+        return Ok(None);
+    };
+
+    // The long values are wrapped on lines starting with a space
+    let mut lines: Vec<String> = Vec::new();
+    for line in manifest.lines() {
+        let line = line?;
+        match (line.strip_prefix(' '), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line),
+        }
+    }
+
+    Ok(lines.into_iter().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case(attribute)
+            .then(|| value.trim().to_string())
+    }))
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(
+    name: &str,
+    json: &[u8],
+) -> Result<T, ModMetadataError> {
+    serde_json::from_slice(json).map_err(|e| ModMetadataError::ParseJson(name.to_string(), e))
+}
+
+/// An icon path, or the paths of the icon for each size.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Icon {
+    Path(String),
+    Sizes(BTreeMap<String, String>),
+}
+
+impl Icon {
+    /// The path of the icon, the largest one if there are several sizes.
+    fn into_path(self) -> Option<String> {
+        match self {
+            Icon::Path(path) => Some(path),
+            Icon::Sizes(sizes) => sizes
+                .into_iter()
+                .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or_default())
+                .map(|(_, path)| path),
+        }
+    }
+}
+
+/// A version range, or several ones any of which is accepted.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum VersionRanges {
+    One(String),
+    Any(Vec<String>),
+}
+
+impl VersionRanges {
+    fn into_range(self) -> String {
+        match self {
+            VersionRanges::One(range) => range,
+            VersionRanges::Any(ranges) if ranges.is_empty() => "*".to_string(),
+            VersionRanges::Any(ranges) => ranges.join(" || "),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct FabricModJson {
+    id: String,
+    version: String,
+    name: Option<String>,
+    description: Option<String>,
+    icon: Option<Icon>,
+    #[serde(default)]
+    depends: BTreeMap<String, VersionRanges>,
+    #[serde(default)]
+    recommends: BTreeMap<String, VersionRanges>,
+    #[serde(default)]
+    suggests: BTreeMap<String, VersionRanges>,
+    #[serde(default)]
+    breaks: BTreeMap<String, VersionRanges>,
+}
+
+impl FabricModJson {
+    fn into_info(self) -> ModInfo {
+        let dependencies = [
+            (self.depends, DependencyKind::Required),
+            (self.recommends, DependencyKind::Optional),
+            (self.suggests, DependencyKind::Optional),
+            (self.breaks, DependencyKind::Incompatible),
+        ]
+        .into_iter()
+        .flat_map(|(dependencies, kind)| {
+            dependencies
+                .into_iter()
+                .map(move |(id, ranges)| ModDependency {
+                    id,
+                    version_range: ranges.into_range(),
+                    kind,
+                })
+        })
+        .collect();
+
+        ModInfo {
+            path: PathBuf::new(),
+            name: self.name.unwrap_or_else(|| self.id.clone()),
+            id: self.id,
+            version: self.version,
+            description: self.description,
+            loader: ModLoader::Fabric,
+            dependencies,
+            icon: self.icon.and_then(Icon::into_path),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuiltLoader {
+    id: String,
+    version: String,
+    #[serde(default)]
+    metadata: QuiltMetadata,
+    #[serde(default)]
+    depends: Vec<QuiltDependency>,
+    #[serde(default)]
+    breaks: Vec<QuiltDependency>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct QuiltMetadata {
+    name: Option<String>,
+    description: Option<String>,
+    icon: Option<Icon>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum QuiltDependency {
+    Id(String),
+    Object {
+        id: String,
+        versions: Option<QuiltVersions>,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+/// The versions accepted by a Quilt dependency: a range, an array of constraints any of which
+/// is accepted, or an `any`/`all` object of constraints.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum QuiltVersions {
+    Range(String),
+    Any(Vec<QuiltVersions>),
+    AnyOf { any: Vec<QuiltVersions> },
+    AllOf { all: Vec<QuiltVersions> },
+}
+
+impl QuiltVersions {
+    /// The constraint as alternatives of space-separated predicates, the `all` objects being
+    /// distributed over the alternatives of their constraints.
+    fn alternatives(self) -> Vec<String> {
+        let any = |versions: Vec<QuiltVersions>| {
+            let alternatives = versions
+                .into_iter()
+                .flat_map(QuiltVersions::alternatives)
+                .collect::<Vec<_>>();
+            match alternatives.is_empty() {
+                true => vec!["*".to_string()],
+                false => alternatives,
+            }
+        };
+
+        match self {
+            QuiltVersions::Range(range) => range
+                .split("||")
+                .map(|alternative| alternative.trim().to_string())
+                .collect(),
+            QuiltVersions::Any(versions) | QuiltVersions::AnyOf { any: versions } => any(versions),
+            QuiltVersions::AllOf { all } => {
+                all.into_iter()
+                    .fold(vec!["*".to_string()], |alternatives, versions| {
+                        let others = versions.alternatives();
+                        alternatives
+                            .iter()
+                            .flat_map(|alternative| {
+                                others
+                                    .iter()
+                                    .map(move |other| match (&**alternative, &**other) {
+                                        ("*" | "", other) => other.to_string(),
+                                        (alternative, "*" | "") => alternative.to_string(),
+                                        (alternative, other) => {
+                                            format!("{} {}", alternative, other)
+                                        }
+                                    })
+                            })
+                            .collect()
+                    })
+            }
+        }
+    }
+}
+
+impl QuiltDependency {
+    fn into_dependency(self, kind: DependencyKind) -> ModDependency {
+        let (id, versions, optional) = match self {
+            QuiltDependency::Id(id) => (id, None, false),
+            QuiltDependency::Object {
+                id,
+                versions,
+                optional,
+            } => (id, versions, optional),
+        };
+        let version_range = versions
+            .map(|versions| versions.alternatives().join(" || "))
+            .unwrap_or_else(|| "*".to_string());
+
+        ModDependency {
+            // The ID may be prefixed by the maven group
+            id: id.rsplit(':').next().unwrap_or_default().to_string(),
+            version_range,
+            kind: match kind {
+                DependencyKind::Required if optional => DependencyKind::Optional,
+                kind => kind,
+            },
+        }
+    }
+}
+
+impl QuiltModJson {
+    fn into_info(self) -> ModInfo {
+        let loader = self.quilt_loader;
+        let dependencies = loader
+            .depends
+            .into_iter()
+            .map(|dependency| dependency.into_dependency(DependencyKind::Required))
+            .chain(
+                loader
+                    .breaks
+                    .into_iter()
+                    .map(|dependency| dependency.into_dependency(DependencyKind::Incompatible)),
+            )
+            .collect();
+
+        ModInfo {
+            path: PathBuf::new(),
+            name: loader.metadata.name.unwrap_or_else(|| loader.id.clone()),
+            id: loader.id,
+            version: loader.version,
+            description: loader.metadata.description,
+            loader: ModLoader::Quilt,
+            dependencies,
+            icon: loader.metadata.icon.and_then(Icon::into_path),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Vec<ModsTomlDependency>>,
+    logo_file: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlMod {
+    mod_id: String,
+    version: Option<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    logo_file: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ModsTomlDependency {
+    mod_id: String,
+    /// Forge only
+    mandatory: Option<bool>,
+    /// NeoForge only: `required`, `optional`, `incompatible` or `discouraged`
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    version_range: Option<String>,
+}
+
+impl ModsTomlDependency {
+    fn kind(&self) -> DependencyKind {
+        // The NeoForge types are written in any case (`REQUIRED`, `required`...)
+        let is = |kind: &str| {
+            self.kind
+                .as_deref()
+                .is_some_and(|value| value.eq_ignore_ascii_case(kind))
+        };
+
+        if is("incompatible") {
+            DependencyKind::Incompatible
+        } else if is("required") || (self.kind.is_none() && self.mandatory != Some(false)) {
+            DependencyKind::Required
+        } else {
+            DependencyKind::Optional
+        }
+    }
+}
+
+impl ModsToml {
+    fn into_infos(mut self, loader: ModLoader, jar_version: Option<&str>) -> Vec<ModInfo> {
+        let logo_file = self.logo_file;
+
+        self.mods
+            .into_iter()
+            .map(|entry| {
+                let dependencies = self
+                    .dependencies
+                    .remove(&entry.mod_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|dependency| ModDependency {
+                        kind: dependency.kind(),
+                        id: dependency.mod_id,
+                        version_range: dependency
+                            .version_range
+                            .filter(|range| !range.is_empty())
+                            .unwrap_or_else(|| "*".to_string()),
+                    })
+                    .collect();
+
+                let version = entry.version.unwrap_or_else(|| "1".to_string());
+                let version = match jar_version {
+                    Some(jar_version) => version.replace("${file.jarVersion}", jar_version),
+                    None => version,
+                };
+
+                ModInfo {
+                    path: PathBuf::new(),
+                    name: entry.display_name.unwrap_or_else(|| entry.mod_id.clone()),
+                    id: entry.mod_id,
+                    version,
+                    description: entry.description.map(|d| d.trim().to_string()),
+                    loader,
+                    dependencies,
+                    icon: entry.logo_file.or_else(|| logo_file.clone()),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum McModInfo {
+    List(Vec<McModInfoEntry>),
+    V2 {
+        #[serde(rename = "modList")]
+        mod_list: Vec<McModInfoEntry>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct McModInfoEntry {
+    modid: String,
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    logo_file: Option<String>,
+    /// The mods needed, as `id` or `id@range`
+    #[serde(default)]
+    required_mods: Vec<String>,
+    /// The mods loaded before this one, as `id` or `id@range`
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl McModInfo {
+    fn into_infos(self) -> Vec<ModInfo> {
+        let entries = match self {
+            McModInfo::List(entries) => entries,
+            McModInfo::V2 { mod_list } => mod_list,
+        };
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                let mut dependencies = entry
+                    .required_mods
+                    .iter()
+                    .map(|spec| legacy_dependency(spec, DependencyKind::Required))
+                    .collect::<Vec<_>>();
+                for spec in &entry.dependencies {
+                    let dependency = legacy_dependency(spec, DependencyKind::Optional);
+                    if !dependencies.iter().any(|d| d.id == dependency.id) {
+                        dependencies.push(dependency);
+                    }
+                }
+
+                ModInfo {
+                    path: PathBuf::new(),
+                    name: entry.name.unwrap_or_else(|| entry.modid.clone()),
+                    id: entry.modid,
+                    version: entry.version.unwrap_or_default(),
+                    description: entry.description,
+                    loader: ModLoader::Forge,
+                    dependencies,
+                    icon: entry
+                        .logo_file
+                        .filter(|logo| !logo.is_empty())
+                        .map(|logo| logo.trim_start_matches('/').to_string()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn legacy_dependency(spec: &str, kind: DependencyKind) -> ModDependency {
+    let (id, range) = spec.split_once('@').unwrap_or((spec, "*"));
+    ModDependency {
+        id: id.to_string(),
+        version_range: range.to_string(),
+        kind,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fabric_mod_json() {
+        let json = serde_json::json!({
+            "schemaVersion": 1,
+            "id": "sodium",
+            "version": "0.5.8",
+            "icon": { "16": "icon-16.png", "128": "icon-128.png", "32": "icon-32.png" },
+            "depends": { "fabricloader": ">=0.12.0", "minecraft": ["1.20.3", "1.20.4"] },
+            "breaks": { "optifabric": "*" }
+        });
+
+        let info = serde_json::from_value::<FabricModJson>(json)
+            .unwrap()
+            .into_info();
+
+        assert_eq!(info.name, "sodium");
+        assert_eq!(info.icon.as_deref(), Some("icon-128.png"));
+        assert_eq!(
+            info.dependencies,
+            [
+                ModDependency {
+                    id: "fabricloader".to_string(),
+                    version_range: ">=0.12.0".to_string(),
+                    kind: DependencyKind::Required
+                },
+                ModDependency {
+                    id: "minecraft".to_string(),
+                    version_range: "1.20.3 || 1.20.4".to_string(),
+                    kind: DependencyKind::Required
+                },
+                ModDependency {
+                    id: "optifabric".to_string(),
+                    version_range: "*".to_string(),
+                    kind: DependencyKind::Incompatible
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_quilt_versions() {
+        let json = serde_json::json!({
+            "schema_version": 1,
+            "quilt_loader": {
+                "id": "example",
+                "version": "1.0.0",
+                "depends": [
+                    "quilt_loader",
+                    { "id": "org.quiltmc:quilted_fabric_api", "versions": ["^7.0.0", "^8.0.0"] },
+                    { "id": "minecraft", "versions": { "any": ["1.20.1", { "all": [">=1.20.3", "<1.20.5"] }] } },
+                    { "id": "lib", "versions": { "all": [">=1.0.0", { "any": ["<2.0.0", ">=3.0.0"] }] } }
+                ]
+            }
+        });
+
+        let info = serde_json::from_value::<QuiltModJson>(json)
+            .unwrap()
+            .into_info();
+
+        let ranges = info
+            .dependencies
+            .iter()
+            .map(|dependency| (dependency.id.as_str(), dependency.version_range.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranges,
+            [
+                ("quilt_loader", "*"),
+                ("quilted_fabric_api", "^7.0.0 || ^8.0.0"),
+                ("minecraft", "1.20.1 || >=1.20.3 <1.20.5"),
+                ("lib", ">=1.0.0 <2.0.0 || >=1.0.0 >=3.0.0")
+            ]
+        );
+
+        // An invalid constraint is an error rather than any version
+        let json = serde_json::json!({
+            "quilt_loader": {
+                "id": "example",
+                "version": "1.0.0",
+                "depends": [{ "id": "lib", "versions": { "none": [] } }]
+            }
+        });
+        assert!(serde_json::from_value::<QuiltModJson>(json).is_err());
+    }
+
+    #[test]
+    fn parse_mods_toml() {
+        let toml = r#"
+            modLoader = "javafml"
+            loaderVersion = "[49,)"
+            logoFile = "logo.png"
+
+            [[mods]]
+            modId = "jei"
+            version = "${file.jarVersion}"
+            displayName = "Just Enough Items"
+
+            [[dependencies.jei]]
+            modId = "forge"
+            mandatory = true
+            versionRange = "[49.0.3,)"
+
+            [[dependencies.jei]]
+            modId = "minecraft"
+            type = "optional"
+            versionRange = ""
+
+            [[dependencies.jei]]
+            modId = "neoforge"
+            type = "REQUIRED"
+            versionRange = "[20.4,)"
+
+            [[dependencies.jei]]
+            modId = "optifine"
+            type = "Incompatible"
+        "#;
+
+        let infos = toml::from_str::<ModsToml>(toml)
+            .unwrap()
+            .into_infos(ModLoader::Forge, Some("17.3.0.49"));
+
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].version, "17.3.0.49");
+        assert_eq!(infos[0].icon.as_deref(), Some("logo.png"));
+        assert_eq!(
+            infos[0].dependencies,
+            [
+                ModDependency {
+                    id: "forge".to_string(),
+                    version_range: "[49.0.3,)".to_string(),
+                    kind: DependencyKind::Required
+                },
+                ModDependency {
+                    id: "minecraft".to_string(),
+                    version_range: "*".to_string(),
+                    kind: DependencyKind::Optional
+                },
+                ModDependency {
+                    id: "neoforge".to_string(),
+                    version_range: "[20.4,)".to_string(),
+                    kind: DependencyKind::Required
+                },
+                ModDependency {
+                    id: "optifine".to_string(),
+                    version_range: "*".to_string(),
+                    kind: DependencyKind::Incompatible
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod curseforge;
+pub mod metadata;
+pub mod modrinth;
+pub mod resolver;
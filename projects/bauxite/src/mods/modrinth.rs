@@ -0,0 +1,426 @@
+//! A client of the [Modrinth API](https://docs.modrinth.com/api/).
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bauxite_download::{check_file_sha512, DownloadError, DownloadInfo, Downloader};
+use reqwest::{
+    header::{HeaderMap, USER_AGENT},
+    StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::Instance;
+
+/// The User-Agent sent to the API, which rejects the generic ones.
+const DEFAULT_USER_AGENT: &str = concat!("bauxite/", env!("CARGO_PKG_VERSION"));
+/// How many times a rate limited request is retried.
+const MAX_RATE_LIMITED_RETRIES: u32 = 3;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ModrinthError {
+    #[error("Failed to query the Modrinth API")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Failed to parse the Modrinth API response")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("Still rate limited after {0} retries")]
+    RateLimited(u32),
+
+    #[error("The version has no file")]
+    NoFile,
+
+    #[error("Invalid file name: {0}")]
+    InvalidFileName(String),
+
+    #[error("Failed to download the file")]
+    DownloadError(#[from] DownloadError),
+}
+
+/// A search of projects, filtered by game version, loader and project type.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    query: String,
+    game_versions: Vec<String>,
+    loaders: Vec<String>,
+    project_type: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl SearchQuery {
+    pub fn new(query: &str) -> Self {
+        SearchQuery {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Only keep the projects with a version for this game version (can be repeated).
+    pub fn with_game_version(mut self, game_version: &str) -> Self {
+        self.game_versions.push(game_version.to_string());
+        self
+    }
+
+    /// Only keep the projects supporting this loader, e.g. `fabric` (can be repeated).
+    pub fn with_loader(mut self, loader: &str) -> Self {
+        self.loaders.push(loader.to_string());
+        self
+    }
+
+    /// Only keep the projects of this type (`mod`, `resourcepack`, `modpack`...).
+    pub fn with_project_type(mut self, project_type: &str) -> Self {
+        self.project_type = Some(project_type.to_string());
+        self
+    }
+
+    /// Set the maximum number of results (10 by default, at most 100).
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first results, for pagination.
+    pub fn with_offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The facets of the search: the values of a facet are OR'ed, the facets are AND'ed.
+    fn facets(&self) -> Vec<Vec<String>> {
+        let mut facets = Vec::new();
+        if !self.game_versions.is_empty() {
+            facets.push(facet("versions", &self.game_versions));
+        }
+        if !self.loaders.is_empty() {
+            facets.push(facet("categories", &self.loaders));
+        }
+        if let Some(project_type) = &self.project_type {
+            facets.push(vec![format!("project_type:{}", project_type)]);
+        }
+        facets
+    }
+
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![("query", self.query.clone())];
+        let facets = self.facets();
+        if !facets.is_empty() {
+            query.push(("facets", serde_json::to_string(&facets).unwrap_or_default()));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            query.push(("offset", offset.to_string()));
+        }
+        query
+    }
+}
+
+fn facet(name: &str, values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| format!("{}:{}", name, value))
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    pub offset: u32,
+    pub limit: u32,
+    pub total_hits: u32,
+}
+
+/// A project found by a search.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SearchHit {
+    pub project_id: String,
+    pub project_type: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub author: String,
+    /// The categories and the loaders of the project
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// The game versions of the project
+    #[serde(default)]
+    pub versions: Vec<String>,
+    pub downloads: u64,
+    pub latest_version: Option<String>,
+}
+
+/// A version of a project.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModrinthVersion {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version_number: String,
+    /// `release`, `beta` or `alpha`
+    pub version_type: String,
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    pub files: Vec<ModrinthFile>,
+    #[serde(default)]
+    pub dependencies: Vec<ModrinthDependency>,
+}
+
+impl ModrinthVersion {
+    /// The main file of the version, or its first file if none is marked as primary.
+    pub fn primary_file(&self) -> Option<&ModrinthFile> {
+        self.files
+            .iter()
+            .find(|file| file.primary)
+            .or_else(|| self.files.first())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModrinthFile {
+    pub hashes: ModrinthHashes,
+    pub url: String,
+    pub filename: String,
+    pub primary: bool,
+    pub size: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModrinthHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModrinthDependency {
+    pub version_id: Option<String>,
+    pub project_id: Option<String>,
+    pub file_name: Option<String>,
+    pub dependency_type: DependencyType,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+    Required,
+    Optional,
+    Incompatible,
+    /// Shipped inside the file of the version
+    Embedded,
+}
+
+/// A client of the Modrinth API.
+///
+/// The requests wait when the API reports the rate limit is reached (`X-Ratelimit-Remaining`
+/// at 0), until it is reset (`X-Ratelimit-Reset` seconds later). The clones share this wait.
+#[derive(Debug, Clone)]
+pub struct ModrinthClient {
+    downloader: Downloader,
+    user_agent: String,
+    /// When the rate limit is reset, if it was reached
+    reset_at: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Default for ModrinthClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModrinthClient {
+    pub fn new() -> Self {
+        ModrinthClient {
+            downloader: Downloader::shared().clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            reset_at: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Set the downloader used for the requests and the files (defaults to
+    /// [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
+    /// Set the User-Agent identifying the launcher, e.g. `my-launcher/1.0 (contact@example.com)`.
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Search projects.
+    pub async fn search(&self, query: &SearchQuery) -> Result<SearchResults, ModrinthError> {
+        self.get("/search", &query.to_query()).await
+    }
+
+    /// List the versions of a project (by ID or slug), newest first, only keeping the ones
+    /// for the given game versions and loaders when they are not empty.
+    pub async fn project_versions(
+        &self,
+        project: &str,
+        game_versions: &[&str],
+        loaders: &[&str],
+    ) -> Result<Vec<ModrinthVersion>, ModrinthError> {
+        let mut query = Vec::new();
+        if !game_versions.is_empty() {
+            query.push(("game_versions", serde_json::to_string(game_versions)?));
+        }
+        if !loaders.is_empty() {
+            query.push(("loaders", serde_json::to_string(loaders)?));
+        }
+
+        self.get(&format!("/project/{}/version", project), &query)
+            .await
+    }
+
+    /// Get a version by ID.
+    pub async fn version(&self, id: &str) -> Result<ModrinthVersion, ModrinthError> {
+        self.get(&format!("/version/{}", id), &[]).await
+    }
+
+    /// Download the primary file of a version into the mods folder of an instance.
+    pub async fn download_version(
+        &self,
+        version: &ModrinthVersion,
+        instance: &Instance,
+    ) -> Result<PathBuf, ModrinthError> {
+        let file = version.primary_file().ok_or(ModrinthError::NoFile)?;
+        self.download_file(file, &instance.mods_dir()).await
+    }
+
+    /// Download a file into a folder, unless it already exists with the right SHA512.
+    pub async fn download_file(
+        &self,
+        file: &ModrinthFile,
+        dir: &Path,
+    ) -> Result<PathBuf, ModrinthError> {
+        let name = Path::new(&file.filename)
+            .file_name()
+            .filter(|name| *name == file.filename.as_str())
+            .ok_or_else(|| ModrinthError::InvalidFileName(file.filename.clone()))?;
+        let path = dir.join(name);
+
+        if path.exists() && check_file_sha512(&path, &file.hashes.sha512).is_err() {
+            debug!("Replacing the outdated file {:?}", path);
+            std::fs::remove_file(&path).map_err(DownloadError::from)?;
+        }
+
+        let info = DownloadInfo {
+            path: path.clone(),
+            url: file.url.clone(),
+            size: file.size,
+            sha1: Some(file.hashes.sha1.clone()),
+        };
+        self.downloader.download(info, &|_: &Path, _| {}).await?;
+
+        if let Err(e) = check_file_sha512(&path, &file.hashes.sha512) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e.into());
+        }
+
+        Ok(path)
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ModrinthError> {
+        let endpoints = self.downloader.endpoints();
+        let url = endpoints.rewrite(&format!("{}{}", endpoints.modrinth_api(), path));
+
+        let mut retries = 0;
+        loop {
+            self.wait_rate_limit().await;
+
+            debug!("Querying {}", url);
+            let response = self
+                .downloader
+                .client()
+                .get(&url)
+                .query(query)
+                .header(USER_AGENT, &self.user_agent)
+                .send()
+                .await?;
+            let limited = self.update_rate_limit(response.status(), response.headers());
+
+            if limited {
+                if retries == MAX_RATE_LIMITED_RETRIES {
+                    return Err(ModrinthError::RateLimited(retries));
+                }
+                retries += 1;
+                continue;
+            }
+
+            let body = response.error_for_status()?.bytes().await?;
+            return Ok(serde_json::from_slice(&body)?);
+        }
+    }
+
+    async fn wait_rate_limit(&self) {
+        let reset_at = *self.reset_at.lock().unwrap();
+        if let Some(reset_at) = reset_at {
+            debug!(
+                "Rate limited by Modrinth, waiting {:?}",
+                reset_at.saturating_duration_since(Instant::now())
+            );
+            tokio::time::sleep_until(reset_at).await;
+        }
+    }
+
+    /// Remember when the rate limit is reset if it is reached, and check if the request was
+    /// rejected because of it.
+    fn update_rate_limit(&self, status: StatusCode, headers: &HeaderMap) -> bool {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        let limited = status == StatusCode::TOO_MANY_REQUESTS;
+
+        let reset_at = if limited || header("x-ratelimit-remaining") == Some(0) {
+            let reset = header("x-ratelimit-reset").unwrap_or(1);
+            Some(Instant::now() + Duration::from_secs(reset))
+        } else {
+            None
+        };
+        *self.reset_at.lock().unwrap() = reset_at;
+
+        limited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_facets() {
+        let query = SearchQuery::new("sodium")
+            .with_game_version("1.20.4")
+            .with_game_version("1.20.3")
+            .with_loader("fabric")
+            .with_project_type("mod");
+
+        assert_eq!(
+            query.to_query(),
+            [
+                ("query", "sodium".to_string()),
+                (
+                    "facets",
+                    r#"[["versions:1.20.4","versions:1.20.3"],["categories:fabric"],["project_type:mod"]]"#
+                        .to_string()
+                )
+            ]
+        );
+    }
+}
@@ -0,0 +1,944 @@
+//! Resolve the mods to install from the requested ones: their required dependencies are
+//! added, and the versions are chosen so that every version range and incompatibility is
+//! satisfied.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    future::Future,
+    sync::Arc,
+};
+
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use super::{
+    metadata::{DependencyKind, ModDependency, ModLoader},
+    modrinth::{self, ModrinthClient, ModrinthError, ModrinthVersion},
+};
+
+mod range;
+pub use range::{InvalidRange, VersionRange};
+
+/// The dependencies provided by the game and the loaders rather than by mods, which are
+/// ignored unless their version is given with [`Resolver::with_provided`].
+const PLATFORM_IDS: [&str; 9] = [
+    "java",
+    "fabricloader",
+    "fabric-loader",
+    "quilt_loader",
+    "forge",
+    "neoforge",
+    "fml",
+    "javafml",
+    "mcp",
+];
+
+/// A version of a mod which can be installed.
+#[derive(Debug, Clone)]
+pub struct ModCandidate<T> {
+    pub id: String,
+    pub version: String,
+    pub dependencies: Vec<ModDependency>,
+    /// What the source needs to install this version
+    pub data: T,
+}
+
+/// Where the versions of the mods come from.
+///
+/// The versions of several mods are listed concurrently.
+pub trait ModSource: Send + Sync + 'static {
+    type Data: Clone + Send + 'static;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// List the versions of a mod available for the game version and the loader, the
+    /// preferred ones first, or nothing if the mod is unknown.
+    fn versions(
+        &self,
+        id: &str,
+        game_version: &str,
+        loader: ModLoader,
+    ) -> impl Future<Output = Result<Vec<ModCandidate<Self::Data>>, Self::Error>> + Send;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResolveError {
+    #[error("Failed to list the versions of {0}")]
+    Source(String, #[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("{0} declares an invalid dependency")]
+    InvalidRange(String, #[source] InvalidRange),
+
+    #[error("{0}")]
+    Conflict(Conflict),
+
+    #[error("Failed to list the versions of the mods")]
+    PoolError(#[from] tokio::task::JoinError),
+}
+
+/// Why the requested mods can't be installed together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The mod has no version for the game version and the loader
+    Missing {
+        id: String,
+        required_by: Option<String>,
+    },
+    /// None of the versions of the mod is in the range
+    NoMatchingVersion {
+        id: String,
+        range: String,
+        required_by: Option<String>,
+        available: Vec<String>,
+    },
+    /// The version of the mod already chosen, or provided by the platform, is not in the range
+    VersionConflict {
+        id: String,
+        range: String,
+        required_by: String,
+        chosen: String,
+    },
+    /// A mod declares it is incompatible with another one
+    Incompatible {
+        id: String,
+        version: String,
+        with: String,
+        with_version: String,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::Missing { id, required_by } => {
+                write!(
+                    f,
+                    "{} is not available for this game version and loader",
+                    id
+                )?;
+                if let Some(required_by) = required_by {
+                    write!(f, " (required by {})", required_by)?;
+                }
+                Ok(())
+            }
+            Conflict::NoMatchingVersion {
+                id,
+                range,
+                required_by,
+                available,
+            } => {
+                match required_by {
+                    Some(required_by) => write!(f, "{} requires {} {}", required_by, id, range)?,
+                    None => write!(f, "{} {} was requested", id, range)?,
+                }
+                write!(
+                    f,
+                    ", but no compatible version matches (available: {})",
+                    available.join(", ")
+                )
+            }
+            Conflict::VersionConflict {
+                id,
+                range,
+                required_by,
+                chosen,
+            } => write!(
+                f,
+                "{} requires {} {}, but {} {} is used",
+                required_by, id, range, id, chosen
+            ),
+            Conflict::Incompatible {
+                id,
+                version,
+                with,
+                with_version,
+            } => write!(
+                f,
+                "{} {} is incompatible with {} {}",
+                id, version, with, with_version
+            ),
+        }
+    }
+}
+
+impl Conflict {
+    /// The mods the conflict is about.
+    fn mods(&self) -> Vec<&str> {
+        match self {
+            Conflict::Missing { id, .. }
+            | Conflict::NoMatchingVersion { id, .. }
+            | Conflict::VersionConflict { id, .. } => vec![id],
+            Conflict::Incompatible { id, with, .. } => vec![id, with],
+        }
+    }
+}
+
+/// The mods to install, the requested ones first.
+#[derive(Debug, Clone)]
+pub struct InstallPlan<T> {
+    pub mods: Vec<ModCandidate<T>>,
+}
+
+/// A mod needed by the requested mods or by the chosen versions.
+#[derive(Debug, Clone)]
+struct Requirement {
+    id: String,
+    range: VersionRange,
+    required_by: Option<String>,
+    /// The ID of the mod requiring it, `None` for the requested mods
+    owner: Option<String>,
+}
+
+/// Why a branch of the search failed: the conflict to report, and the mods whose chosen
+/// versions lead to it. Changing the version of any other mod can't solve it, so the search
+/// jumps back over them.
+struct Failure {
+    conflict: Conflict,
+    culprits: HashSet<String>,
+}
+
+impl Failure {
+    fn new<'a>(conflict: Conflict, culprits: impl IntoIterator<Item = &'a String>) -> Self {
+        Failure {
+            conflict,
+            culprits: culprits.into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Resolve the mods to install from a [`ModSource`].
+pub struct Resolver<S: ModSource> {
+    source: Arc<S>,
+    game_version: String,
+    loader: ModLoader,
+    /// The versions of the game and the loader
+    provided: HashMap<String, String>,
+}
+
+impl<S: ModSource> Resolver<S> {
+    /// Create a resolver for the given game version and loader.
+    pub fn new(source: S, game_version: &str, loader: ModLoader) -> Self {
+        Resolver {
+            source: Arc::new(source),
+            game_version: game_version.to_string(),
+            loader,
+            provided: HashMap::from([("minecraft".to_string(), game_version.to_string())]),
+        }
+    }
+
+    /// Check the dependencies on a platform component, like the loader (`fabricloader`,
+    /// `forge`...) or `java`, against its version.
+    pub fn with_provided(mut self, id: &str, version: &str) -> Self {
+        self.provided.insert(id.to_string(), version.to_string());
+        self
+    }
+
+    /// Choose the versions of the requested mods and of their required dependencies.
+    pub async fn resolve(&self, requested: &[&str]) -> Result<InstallPlan<S::Data>, ResolveError> {
+        let universe = self.fetch_universe(requested).await?;
+
+        let pending = requested
+            .iter()
+            .map(|id| Requirement {
+                id: id.to_string(),
+                range: VersionRange::any(),
+                required_by: None,
+                owner: None,
+            })
+            .collect();
+
+        let mut search = Search {
+            resolver: self,
+            universe: &universe,
+            chosen: Vec::new(),
+        };
+        match search.solve(pending)? {
+            Ok(()) => Ok(InstallPlan {
+                mods: search.chosen.into_iter().cloned().collect(),
+            }),
+            Err(failure) => Err(ResolveError::Conflict(failure.conflict)),
+        }
+    }
+
+    /// List the versions of the requested mods, and of every mod the versions which may be
+    /// chosen require.
+    ///
+    /// The mods are listed by layers, concurrently: a version may be chosen if it matches one
+    /// of the ranges its mod is required with.
+    async fn fetch_universe(
+        &self,
+        requested: &[&str],
+    ) -> Result<HashMap<String, Vec<ModCandidate<S::Data>>>, ResolveError> {
+        let mut universe: HashMap<String, Vec<ModCandidate<S::Data>>> = HashMap::new();
+        let mut ranges: HashMap<String, Vec<VersionRange>> = requested
+            .iter()
+            .map(|id| (id.to_string(), vec![VersionRange::any()]))
+            .collect();
+        let mut expanded: HashSet<(String, String)> = HashSet::new();
+        let mut layer: HashSet<String> = requested.iter().map(|id| id.to_string()).collect();
+
+        while !layer.is_empty() {
+            let mut joinset = JoinSet::new();
+            for id in layer.drain() {
+                if universe.contains_key(&id) || self.is_platform(&id) {
+                    continue;
+                }
+
+                debug!("Listing the versions of {}", id);
+                let source = self.source.clone();
+                let game_version = self.game_version.clone();
+                let loader = self.loader;
+                joinset.spawn(async move {
+                    let result = source.versions(&id, &game_version, loader).await;
+                    (id, result)
+                });
+            }
+            while let Some(result) = joinset.join_next().await {
+                let (id, result) = result?;
+                let candidates =
+                    result.map_err(|e| ResolveError::Source(id.clone(), Box::new(e)))?;
+                universe.insert(id, candidates);
+            }
+
+            // The dependencies of the versions which may now be chosen form the next layer.
+            // New ranges may make the versions of the mods already listed choosable, so every
+            // mod is checked until nothing changes.
+            loop {
+                let mut changed = false;
+                for (id, candidates) in &universe {
+                    let Some(id_ranges) = ranges.get(id) else {
+                        continue;
+                    };
+                    let choosable = candidates
+                        .iter()
+                        .filter(|candidate| {
+                            id_ranges
+                                .iter()
+                                .any(|range| range.matches(&candidate.version))
+                        })
+                        .filter(|candidate| {
+                            !expanded.contains(&(id.clone(), candidate.version.clone()))
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut dependencies = Vec::new();
+                    for candidate in choosable {
+                        expanded.insert((id.clone(), candidate.version.clone()));
+                        for dependency in &candidate.dependencies {
+                            if dependency.kind != DependencyKind::Required {
+                                continue;
+                            }
+                            // The invalid ranges are reported by the search
+                            let range = self
+                                .parse_range(dependency, candidate)
+                                .unwrap_or_else(|_| VersionRange::any());
+                            dependencies.push((dependency.id.clone(), range));
+                        }
+                    }
+
+                    for (dependency, range) in dependencies {
+                        if !universe.contains_key(&dependency) {
+                            layer.insert(dependency.clone());
+                        }
+                        ranges.entry(dependency).or_default().push(range);
+                        changed = true;
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+        }
+
+        Ok(universe)
+    }
+
+    fn is_platform(&self, id: &str) -> bool {
+        self.provided.contains_key(id) || PLATFORM_IDS.contains(&id.to_lowercase().as_str())
+    }
+
+    fn parse_range(
+        &self,
+        dependency: &ModDependency,
+        candidate: &ModCandidate<S::Data>,
+    ) -> Result<VersionRange, ResolveError> {
+        VersionRange::parse(&dependency.version_range, self.loader)
+            .map_err(|e| ResolveError::InvalidRange(describe(candidate), e))
+    }
+}
+
+fn describe<T>(candidate: &ModCandidate<T>) -> String {
+    format!("{} {}", candidate.id, candidate.version)
+}
+
+/// A backtracking search of the versions to install.
+struct Search<'a, S: ModSource> {
+    resolver: &'a Resolver<S>,
+    universe: &'a HashMap<String, Vec<ModCandidate<S::Data>>>,
+    chosen: Vec<&'a ModCandidate<S::Data>>,
+}
+
+impl<'a, S: ModSource> Search<'a, S> {
+    /// The chosen version of a mod, by the ID of its dependents or of its versions.
+    fn chosen(&self, id: &str) -> Option<&'a ModCandidate<S::Data>> {
+        let id = self.chosen_id(id);
+        self.chosen.iter().copied().find(|chosen| chosen.id == id)
+    }
+
+    /// Satisfy the pending requirements, returning the conflict which prevented it if there
+    /// is no solution.
+    ///
+    /// The versions are tried in order, backtracking on conflicts, but only to the mods
+    /// causing them: trying the other versions of the mods in between can't help.
+    fn solve(
+        &mut self,
+        mut pending: VecDeque<Requirement>,
+    ) -> Result<Result<(), Failure>, ResolveError> {
+        let Some(requirement) = pending.pop_front() else {
+            return Ok(Ok(()));
+        };
+        let required_by = || {
+            requirement
+                .required_by
+                .clone()
+                .unwrap_or_else(|| "The request".to_string())
+        };
+
+        if let Some(version) = self.resolver.provided.get(&requirement.id) {
+            if !requirement.range.matches(version) {
+                let conflict = Conflict::VersionConflict {
+                    id: requirement.id.clone(),
+                    range: requirement.range.to_string(),
+                    required_by: required_by(),
+                    chosen: version.clone(),
+                };
+                return Ok(Err(Failure::new(conflict, &requirement.owner)));
+            }
+            return self.solve(pending);
+        }
+        if self.resolver.is_platform(&requirement.id) {
+            return self.solve(pending);
+        }
+
+        if let Some(chosen) = self.chosen(&requirement.id) {
+            if !requirement.range.matches(&chosen.version) {
+                let conflict = Conflict::VersionConflict {
+                    id: chosen.id.clone(),
+                    range: requirement.range.to_string(),
+                    required_by: required_by(),
+                    chosen: chosen.version.clone(),
+                };
+                let culprits = requirement.owner.iter().chain([&chosen.id]);
+                return Ok(Err(Failure::new(conflict, culprits)));
+            }
+            return self.solve(pending);
+        }
+
+        let candidates = self
+            .universe
+            .get(&requirement.id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if candidates.is_empty() {
+            let conflict = Conflict::Missing {
+                id: requirement.id.clone(),
+                required_by: requirement.required_by.clone(),
+            };
+            return Ok(Err(Failure::new(conflict, &requirement.owner)));
+        }
+        let id = self.chosen_id(&requirement.id).to_string();
+
+        let mut conflicts = Vec::new();
+        let mut culprits: HashSet<String> = requirement.owner.iter().cloned().collect();
+        for candidate in candidates {
+            if !requirement.range.matches(&candidate.version) {
+                continue;
+            }
+            if let Some(incompatible) = self.incompatibility(candidate)? {
+                culprits.extend(incompatible.mods().into_iter().map(str::to_string));
+                conflicts.push(incompatible);
+                continue;
+            }
+
+            let mut next = pending.clone();
+            for dependency in &candidate.dependencies {
+                if dependency.kind == DependencyKind::Required {
+                    next.push_back(Requirement {
+                        id: dependency.id.clone(),
+                        range: self.resolver.parse_range(dependency, candidate)?,
+                        required_by: Some(describe(candidate)),
+                        owner: Some(candidate.id.clone()),
+                    });
+                }
+            }
+
+            self.chosen.push(candidate);
+            let failure = match self.solve(next)? {
+                Ok(()) => return Ok(Ok(())),
+                Err(failure) => failure,
+            };
+            self.chosen.pop();
+
+            if !failure.culprits.contains(&id) {
+                debug!(
+                    "Jumping back over {}: {}",
+                    describe(candidate),
+                    failure.conflict
+                );
+                return Ok(Err(failure));
+            }
+            debug!(
+                "Backtracking from {}: {}",
+                describe(candidate),
+                failure.conflict
+            );
+            culprits.extend(failure.culprits);
+            conflicts.push(failure.conflict);
+        }
+        culprits.remove(&id);
+
+        // The conflicts about this mod explain best why none of its versions fits
+        let conflict = match conflicts
+            .iter()
+            .rposition(|conflict| conflict.mods().contains(&id.as_str()))
+        {
+            Some(index) => Some(conflicts.swap_remove(index)),
+            None => conflicts.pop(),
+        };
+        let conflict = conflict.unwrap_or_else(|| Conflict::NoMatchingVersion {
+            id: requirement.id.clone(),
+            range: requirement.range.to_string(),
+            required_by: requirement.required_by.clone(),
+            available: candidates.iter().map(|c| c.version.clone()).collect(),
+        });
+
+        Ok(Err(Failure { conflict, culprits }))
+    }
+
+    /// Check if a version is incompatible with the chosen ones or the platform, either way.
+    fn incompatibility(
+        &self,
+        candidate: &'a ModCandidate<S::Data>,
+    ) -> Result<Option<Conflict>, ResolveError> {
+        for dependency in &candidate.dependencies {
+            if dependency.kind != DependencyKind::Incompatible {
+                continue;
+            }
+            let range = self.resolver.parse_range(dependency, candidate)?;
+
+            let other = match self.resolver.provided.get(&dependency.id) {
+                Some(version) => Some((dependency.id.clone(), version.clone())),
+                None => self
+                    .chosen(&dependency.id)
+                    .map(|chosen| (chosen.id.clone(), chosen.version.clone())),
+            };
+            if let Some((with, with_version)) = other {
+                if range.matches(&with_version) {
+                    return Ok(Some(Conflict::Incompatible {
+                        id: candidate.id.clone(),
+                        version: candidate.version.clone(),
+                        with,
+                        with_version,
+                    }));
+                }
+            }
+        }
+
+        for chosen in &self.chosen {
+            for dependency in &chosen.dependencies {
+                if dependency.kind != DependencyKind::Incompatible
+                    || self.chosen_id(&dependency.id) != candidate.id
+                {
+                    continue;
+                }
+                if self
+                    .resolver
+                    .parse_range(dependency, chosen)?
+                    .matches(&candidate.version)
+                {
+                    return Ok(Some(Conflict::Incompatible {
+                        id: chosen.id.clone(),
+                        version: chosen.version.clone(),
+                        with: candidate.id.clone(),
+                        with_version: candidate.version.clone(),
+                    }));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The ID of the versions of a mod, which may differ from the one used by its dependents.
+    fn chosen_id<'b>(&'b self, id: &'b str) -> &'b str {
+        self.universe
+            .get(id)
+            .and_then(|candidates| candidates.first())
+            .map_or(id, |candidate| candidate.id.as_str())
+    }
+}
+
+/// The Modrinth projects, identified by ID or slug. Their dependencies either accept any
+/// version of a project, or pin one of its versions by ID, which is fetched to find its
+/// project and its version number.
+impl ModSource for ModrinthClient {
+    type Data = ModrinthVersion;
+    type Error = ModrinthError;
+
+    async fn versions(
+        &self,
+        id: &str,
+        game_version: &str,
+        loader: ModLoader,
+    ) -> Result<Vec<ModCandidate<ModrinthVersion>>, ModrinthError> {
+        let loader_name = match loader {
+            ModLoader::Fabric => "fabric",
+            ModLoader::Quilt => "quilt",
+            ModLoader::Forge => "forge",
+            ModLoader::NeoForge => "neoforge",
+        };
+        let versions = match self
+            .project_versions(id, &[game_version], &[loader_name])
+            .await
+        {
+            Ok(versions) => versions,
+            Err(ModrinthError::HttpError(e))
+                if e.status() == Some(reqwest::StatusCode::NOT_FOUND) =>
+            {
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut pinned = HashMap::new();
+        for version_id in versions
+            .iter()
+            .flat_map(|version| &version.dependencies)
+            .filter_map(|dependency| dependency.version_id.as_ref())
+        {
+            if !pinned.contains_key(version_id) {
+                let version = self.version(version_id).await?;
+                pinned.insert(version_id.clone(), version);
+            }
+        }
+
+        Ok(versions
+            .into_iter()
+            .map(|version| ModCandidate {
+                id: version.project_id.clone(),
+                version: version.version_number.clone(),
+                dependencies: version
+                    .dependencies
+                    .iter()
+                    .filter_map(|dependency| {
+                        let kind = match dependency.dependency_type {
+                            modrinth::DependencyType::Required => DependencyKind::Required,
+                            modrinth::DependencyType::Optional => DependencyKind::Optional,
+                            modrinth::DependencyType::Incompatible => DependencyKind::Incompatible,
+                            modrinth::DependencyType::Embedded => return None,
+                        };
+                        let (id, version_range) = match &dependency.version_id {
+                            Some(version_id) => {
+                                let pinned = &pinned[version_id];
+                                (
+                                    pinned.project_id.clone(),
+                                    exact_range(&pinned.version_number, loader),
+                                )
+                            }
+                            None => (dependency.project_id.clone()?, "*".to_string()),
+                        };
+                        Some(ModDependency {
+                            id,
+                            version_range,
+                            kind,
+                        })
+                    })
+                    .collect(),
+                data: version,
+            })
+            .collect())
+    }
+}
+
+/// The range matching only a version, in the syntax of the loader.
+fn exact_range(version: &str, loader: ModLoader) -> String {
+    match loader {
+        ModLoader::Fabric | ModLoader::Quilt => format!("={}", version),
+        ModLoader::Forge | ModLoader::NeoForge => format!("[{}]", version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A source listing the versions given as `(id, version, dependencies)`.
+    struct FakeSource(Vec<(&'static str, &'static str, Vec<ModDependency>)>);
+
+    impl ModSource for FakeSource {
+        type Data = ();
+        type Error = std::convert::Infallible;
+
+        async fn versions(
+            &self,
+            id: &str,
+            _: &str,
+            _: ModLoader,
+        ) -> Result<Vec<ModCandidate<()>>, Self::Error> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|(candidate, _, _)| *candidate == id)
+                .map(|(id, version, dependencies)| ModCandidate {
+                    id: id.to_string(),
+                    version: version.to_string(),
+                    dependencies: dependencies.clone(),
+                    data: (),
+                })
+                .collect())
+        }
+    }
+
+    /// A source recording the mods whose versions are listed.
+    struct RecordingSource(FakeSource, Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl ModSource for RecordingSource {
+        type Data = ();
+        type Error = std::convert::Infallible;
+
+        async fn versions(
+            &self,
+            id: &str,
+            game_version: &str,
+            loader: ModLoader,
+        ) -> Result<Vec<ModCandidate<()>>, Self::Error> {
+            self.1.lock().unwrap().push(id.to_string());
+            self.0.versions(id, game_version, loader).await
+        }
+    }
+
+    fn dependency(id: &str, version_range: &str, kind: DependencyKind) -> ModDependency {
+        ModDependency {
+            id: id.to_string(),
+            version_range: version_range.to_string(),
+            kind,
+        }
+    }
+
+    fn requires(id: &str, version_range: &str) -> ModDependency {
+        dependency(id, version_range, DependencyKind::Required)
+    }
+
+    async fn resolve(
+        source: FakeSource,
+        loader: ModLoader,
+        requested: &[&str],
+    ) -> Result<Vec<String>, ResolveError> {
+        let plan = Resolver::new(source, "1.20.4", loader)
+            .resolve(requested)
+            .await?;
+        Ok(plan.mods.iter().map(describe).collect())
+    }
+
+    #[tokio::test]
+    async fn pull_transitive_dependencies() {
+        let source = FakeSource(vec![
+            (
+                "a",
+                "2.0.0",
+                vec![
+                    requires("b", ">=1.1"),
+                    requires("fabricloader", ">=0.15.0"),
+                    dependency("modmenu", "*", DependencyKind::Optional),
+                ],
+            ),
+            ("b", "1.2.0", vec![requires("c", "*")]),
+            ("b", "1.0.0", vec![]),
+            ("c", "1.0.0", vec![requires("minecraft", "1.20.x")]),
+        ]);
+
+        assert_eq!(
+            resolve(source, ModLoader::Fabric, &["a"]).await.unwrap(),
+            ["a 2.0.0", "b 1.2.0", "c 1.0.0"]
+        );
+    }
+
+    #[tokio::test]
+    async fn backtrack_on_version_conflict() {
+        let source = FakeSource(vec![
+            ("a", "2.0", vec![requires("c", "[2.0,)")]),
+            ("a", "1.0", vec![requires("c", "[1.0,2.0)")]),
+            ("b", "1.0", vec![requires("c", "[1.0,2.0)")]),
+            ("c", "2.1", vec![]),
+            ("c", "1.5", vec![]),
+        ]);
+
+        assert_eq!(
+            resolve(source, ModLoader::Forge, &["a", "b"])
+                .await
+                .unwrap(),
+            ["a 1.0", "b 1.0", "c 1.5"]
+        );
+    }
+
+    #[tokio::test]
+    async fn explain_conflicts() {
+        let conflict = |result: Result<Vec<String>, ResolveError>| match result {
+            Err(ResolveError::Conflict(conflict)) => conflict.to_string(),
+            result => panic!("{:?}", result),
+        };
+
+        let source = FakeSource(vec![
+            (
+                "a",
+                "1.0.0",
+                vec![dependency("b", "<2", DependencyKind::Incompatible)],
+            ),
+            ("b", "1.5.0", vec![]),
+        ]);
+        assert_eq!(
+            conflict(resolve(source, ModLoader::Fabric, &["a", "b"]).await),
+            "a 1.0.0 is incompatible with b 1.5.0"
+        );
+
+        let source = FakeSource(vec![("a", "1.0.0", vec![requires("x", "*")])]);
+        assert_eq!(
+            conflict(resolve(source, ModLoader::Fabric, &["a"]).await),
+            "x is not available for this game version and loader (required by a 1.0.0)"
+        );
+
+        let source = FakeSource(vec![(
+            "a",
+            "1.0",
+            vec![requires("minecraft", "[1.19,1.20)")],
+        )]);
+        assert_eq!(
+            conflict(resolve(source, ModLoader::NeoForge, &["a"]).await),
+            "a 1.0 requires minecraft [1.19,1.20), but minecraft 1.20.4 is used"
+        );
+
+        let source = FakeSource(vec![
+            ("a", "1.0.0", vec![requires("b", ">=2.0.0")]),
+            ("b", "1.0.0", vec![]),
+            ("b", "1.1.0", vec![]),
+        ]);
+        assert_eq!(
+            conflict(resolve(source, ModLoader::Quilt, &["a"]).await),
+            "a 1.0.0 requires b >=2.0.0, but no compatible version matches (available: 1.0.0, 1.1.0)"
+        );
+    }
+
+    #[tokio::test]
+    async fn explain_conflict_of_the_failing_mod() {
+        let source = FakeSource(vec![
+            ("a", "2.0.0", vec![requires("x", "*")]),
+            ("a", "1.0.0", vec![requires("c", "*")]),
+            (
+                "c",
+                "1.0.0",
+                vec![dependency("a", "*", DependencyKind::Incompatible)],
+            ),
+        ]);
+
+        let result = resolve(source, ModLoader::Fabric, &["a"]).await;
+        // The missing dependency of the first version is not the last conflict about `a`
+        assert!(
+            matches!(
+                &result,
+                Err(ResolveError::Conflict(conflict))
+                    if conflict.to_string() == "c 1.0.0 is incompatible with a 1.0.0"
+            ),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn jump_back_over_unrelated_mods() {
+        const VERSIONS: [&str; 6] = ["1.5.0", "1.4.0", "1.3.0", "1.2.0", "1.1.0", "1.0.0"];
+        const MODS: [&str; 10] = ["m0", "m1", "m2", "m3", "m4", "m5", "m6", "m7", "m8", "m9"];
+
+        // Without jumping back over the unrelated mods, every combination of their versions
+        // would be tried
+        let source = |last: Vec<(&'static str, &'static str, Vec<ModDependency>)>| {
+            let mods = MODS
+                .iter()
+                .flat_map(|id| VERSIONS.iter().map(|version| (*id, *version, vec![])));
+            FakeSource(mods.chain(last).collect())
+        };
+        // The search runs on its own thread, so that the test fails rather than hangs
+        let resolve = |source| {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                let requested = MODS.iter().copied().chain(["y"]).collect::<Vec<_>>();
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                let result = runtime.block_on(
+                    Resolver::new(source, "1.20.4", ModLoader::Fabric).resolve(&requested),
+                );
+                let _ = sender.send(result.map(|plan| plan.mods.len()));
+            });
+            receiver
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .expect("the search does not jump back over the unrelated mods")
+        };
+
+        let incompatible = source(vec![(
+            "y",
+            "1.0.0",
+            vec![dependency("m0", "*", DependencyKind::Incompatible)],
+        )]);
+        match resolve(incompatible) {
+            Err(ResolveError::Conflict(conflict)) => assert_eq!(
+                conflict.to_string(),
+                "y 1.0.0 is incompatible with m0 1.0.0"
+            ),
+            result => panic!("{:?}", result),
+        }
+
+        let unsatisfiable = source(vec![
+            ("y", "1.0.0", vec![requires("w", ">=2.0.0")]),
+            ("w", "1.0.0", vec![]),
+        ]);
+        match resolve(unsatisfiable) {
+            Err(ResolveError::Conflict(conflict)) => assert_eq!(
+                conflict.to_string(),
+                "y 1.0.0 requires w >=2.0.0, but no compatible version matches (available: 1.0.0)"
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_only_choosable_versions() {
+        let fetched = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let source = RecordingSource(
+            FakeSource(vec![
+                (
+                    "a",
+                    "1.0.0",
+                    vec![requires("b", ">=2.0.0"), requires("c", "*")],
+                ),
+                ("b", "2.0.0", vec![]),
+                ("b", "1.0.0", vec![requires("old", "*")]),
+                ("c", "1.0.0", vec![requires("d", "*")]),
+                ("d", "1.0.0", vec![]),
+            ]),
+            fetched.clone(),
+        );
+
+        let plan = Resolver::new(source, "1.20.4", ModLoader::Fabric)
+            .resolve(&["a"])
+            .await
+            .unwrap();
+        assert_eq!(
+            plan.mods.iter().map(describe).collect::<Vec<_>>(),
+            ["a 1.0.0", "b 2.0.0", "c 1.0.0", "d 1.0.0"]
+        );
+
+        let mut fetched = fetched.lock().unwrap().clone();
+        fetched.sort();
+        assert_eq!(fetched, ["a", "b", "c", "d"]);
+    }
+}
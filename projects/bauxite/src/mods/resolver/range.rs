@@ -0,0 +1,486 @@
+//! The version ranges of the mod dependencies: the semver predicates of Fabric and Quilt
+//! (`>=1.2.0 <2`, `^1.2`, `~1.2.3`, `1.20.x`, `*`), and the maven ranges of Forge and NeoForge
+//! (`[1.0,2.0)`, `(,1.5]`, `[1.0]`).
+
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::mods::metadata::ModLoader;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid version range: {0}")]
+pub struct InvalidRange(pub String);
+
+/// A parsed version range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    raw: String,
+    kind: RangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RangeKind {
+    Any,
+    /// Any of the sets of predicates, all of the predicates of a set matching
+    Semver(Vec<Vec<Predicate>>),
+    /// Any of the restrictions
+    Maven(Vec<Restriction>),
+}
+
+impl VersionRange {
+    /// A range matching any version.
+    pub fn any() -> Self {
+        VersionRange {
+            raw: "*".to_string(),
+            kind: RangeKind::Any,
+        }
+    }
+
+    /// Parse a range in the syntax of the loader.
+    pub fn parse(range: &str, loader: ModLoader) -> Result<Self, InvalidRange> {
+        let raw = range.trim();
+        let kind = match raw {
+            "" | "*" => RangeKind::Any,
+            _ => match loader {
+                ModLoader::Fabric | ModLoader::Quilt => RangeKind::Semver(
+                    raw.split("||")
+                        .map(|predicates| {
+                            predicates
+                                .split_whitespace()
+                                .map(|predicate| Predicate::parse(predicate, raw))
+                                .collect::<Result<Vec<_>, _>>()
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                ModLoader::Forge | ModLoader::NeoForge => parse_maven(raw)?,
+            },
+        };
+
+        Ok(VersionRange {
+            raw: raw.to_string(),
+            kind,
+        })
+    }
+
+    /// Check if a version is in the range.
+    pub fn matches(&self, version: &str) -> bool {
+        match &self.kind {
+            RangeKind::Any => true,
+            RangeKind::Semver(sets) => {
+                let parsed = SemVer::parse(version);
+                sets.iter().any(|set| {
+                    set.iter()
+                        .all(|predicate| predicate.matches(version, parsed.as_ref()))
+                })
+            }
+            RangeKind::Maven(restrictions) => {
+                let version = MavenVersion::parse(version);
+                restrictions
+                    .iter()
+                    .any(|restriction| restriction.matches(&version))
+            }
+        }
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// A semantic version, whose release may have any number of components (missing ones are 0).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SemVer {
+    release: Vec<u64>,
+    pre: Vec<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+impl SemVer {
+    fn parse(version: &str) -> Option<Self> {
+        let version = version.split('+').next().unwrap_or_default();
+        let (release, pre) = match version.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (version, None),
+        };
+
+        let release = release
+            .split('.')
+            .map(|component| component.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let pre = pre
+            .map(|pre| {
+                pre.split('.')
+                    .map(|identifier| match identifier.parse::<u64>() {
+                        Ok(number) => Identifier::Numeric(number),
+                        Err(_) => Identifier::Alphanumeric(identifier.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(SemVer { release, pre })
+    }
+
+    fn component(&self, index: usize) -> u64 {
+        self.release.get(index).copied().unwrap_or_default()
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        (0..len)
+            .map(|i| self.component(i).cmp(&other.component(i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    /// Same major version
+    Caret,
+    /// Same major and minor versions
+    Tilde,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    Compare {
+        operator: Operator,
+        raw: String,
+        version: Option<SemVer>,
+    },
+    /// The first components are equal (`1.20.x`)
+    Prefix(Vec<u64>),
+}
+
+impl Predicate {
+    fn parse(predicate: &str, range: &str) -> Result<Self, InvalidRange> {
+        let invalid = || InvalidRange(range.to_string());
+
+        let operators = [
+            (">=", Operator::GreaterOrEqual),
+            ("<=", Operator::LessOrEqual),
+            (">", Operator::Greater),
+            ("<", Operator::Less),
+            ("=", Operator::Equal),
+            ("^", Operator::Caret),
+            ("~", Operator::Tilde),
+        ];
+        let (operator, version) = operators
+            .iter()
+            .find_map(|(prefix, operator)| {
+                predicate
+                    .strip_prefix(prefix)
+                    .map(|version| (*operator, version))
+            })
+            .unwrap_or((Operator::Equal, predicate));
+        if version.is_empty() {
+            return Err(invalid());
+        }
+
+        let components = version.split('.').collect::<Vec<_>>();
+        if let Some(wildcard) = components
+            .iter()
+            .position(|c| matches!(*c, "x" | "X" | "*"))
+        {
+            if operator != Operator::Equal {
+                return Err(invalid());
+            }
+            let prefix = components[..wildcard]
+                .iter()
+                .map(|c| c.parse::<u64>().map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Predicate::Prefix(prefix));
+        }
+
+        let parsed = SemVer::parse(version);
+        if parsed.is_none() && operator != Operator::Equal {
+            return Err(invalid());
+        }
+
+        Ok(Predicate::Compare {
+            operator,
+            raw: version.to_string(),
+            version: parsed,
+        })
+    }
+
+    fn matches(&self, raw_version: &str, version: Option<&SemVer>) -> bool {
+        match self {
+            Predicate::Prefix(prefix) => version.is_some_and(|version| {
+                prefix
+                    .iter()
+                    .enumerate()
+                    .all(|(i, component)| version.component(i) == *component)
+            }),
+            // The versions which are not semantic versions can only be equal
+            Predicate::Compare {
+                raw, version: None, ..
+            } => raw == raw_version,
+            Predicate::Compare {
+                operator,
+                version: Some(expected),
+                ..
+            } => {
+                let Some(version) = version else {
+                    return false;
+                };
+                match operator {
+                    Operator::Equal => version == expected,
+                    Operator::Greater => version > expected,
+                    Operator::GreaterOrEqual => version >= expected,
+                    Operator::Less => version < expected,
+                    Operator::LessOrEqual => version <= expected,
+                    Operator::Caret => {
+                        version >= expected && version.component(0) == expected.component(0)
+                    }
+                    Operator::Tilde => {
+                        version >= expected
+                            && version.component(0) == expected.component(0)
+                            && version.component(1) == expected.component(1)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A maven version, compared like the maven `ComparableVersion`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MavenVersion(Vec<MavenItem>);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum MavenItem {
+    /// A qualifier, ranked by its well-known order (`alpha` < `beta` < `rc` < release)
+    Qualifier(u8, String),
+    Number(u64),
+}
+
+/// The rank of the release qualifier (e.g. `1.0` is `1.0-final`).
+const RELEASE_RANK: u8 = 5;
+
+impl MavenVersion {
+    fn parse(version: &str) -> Self {
+        // The items are separated by `.`, `-` and `_`, and between digits and letters
+        let mut raw_items = Vec::<String>::new();
+        let mut previous: Option<char> = None;
+        for c in version.to_lowercase().chars() {
+            if matches!(c, '.' | '-' | '_') {
+                previous = None;
+                continue;
+            }
+            match (previous, raw_items.last_mut()) {
+                (Some(p), Some(item)) if p.is_ascii_digit() == c.is_ascii_digit() => item.push(c),
+                _ => raw_items.push(c.to_string()),
+            }
+            previous = Some(c);
+        }
+
+        let items = raw_items
+            .iter()
+            .map(|item| match item.parse::<u64>() {
+                Ok(number) => MavenItem::Number(number),
+                Err(_) => {
+                    let rank = match item.as_str() {
+                        "alpha" | "a" => 0,
+                        "beta" | "b" => 1,
+                        "milestone" | "m" => 2,
+                        "rc" | "cr" => 3,
+                        "snapshot" => 4,
+                        "ga" | "final" | "release" => RELEASE_RANK,
+                        "sp" => 6,
+                        _ => 7,
+                    };
+                    MavenItem::Qualifier(rank, item.to_string())
+                }
+            })
+            .collect();
+
+        MavenVersion(items)
+    }
+}
+
+impl Ord for MavenVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        for i in 0..len {
+            let ordering = match (self.0.get(i), other.0.get(i)) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(item), None) => missing_cmp(item),
+                (None, Some(item)) => missing_cmp(item).reverse(),
+                (None, None) => Ordering::Equal,
+            };
+            if ordering.is_ne() {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for MavenVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare an item to a missing one, which is `0` or the release qualifier.
+fn missing_cmp(item: &MavenItem) -> Ordering {
+    match item {
+        MavenItem::Number(number) => number.cmp(&0),
+        MavenItem::Qualifier(rank, _) => rank.cmp(&RELEASE_RANK),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Restriction {
+    /// The lower bound, and if it is inclusive
+    lower: Option<(MavenVersion, bool)>,
+    /// The upper bound, and if it is inclusive
+    upper: Option<(MavenVersion, bool)>,
+}
+
+impl Restriction {
+    fn matches(&self, version: &MavenVersion) -> bool {
+        let above = match &self.lower {
+            Some((lower, true)) => version >= lower,
+            Some((lower, false)) => version > lower,
+            None => true,
+        };
+        let below = match &self.upper {
+            Some((upper, true)) => version <= upper,
+            Some((upper, false)) => version < upper,
+            None => true,
+        };
+        above && below
+    }
+}
+
+/// Parse the union of maven ranges. A single version without brackets is only a
+/// recommendation, so any version matches.
+fn parse_maven(range: &str) -> Result<RangeKind, InvalidRange> {
+    let invalid = || InvalidRange(range.to_string());
+    if !range.starts_with(['[', '(']) {
+        return Ok(RangeKind::Any);
+    }
+
+    let mut restrictions = Vec::new();
+    let mut rest = range;
+    while !rest.is_empty() {
+        let end = rest.find([']', ')']).ok_or_else(invalid)?;
+        let lower_inclusive = match rest.as_bytes()[0] {
+            b'[' => true,
+            b'(' => false,
+            _ => return Err(invalid()),
+        };
+        let upper_inclusive = rest.as_bytes()[end] == b']';
+        let bounds = &rest[1..end];
+
+        let restriction = match bounds.split_once(',') {
+            None => {
+                if !lower_inclusive || !upper_inclusive || bounds.trim().is_empty() {
+                    return Err(invalid());
+                }
+                let version = MavenVersion::parse(bounds.trim());
+                Restriction {
+                    lower: Some((version.clone(), true)),
+                    upper: Some((version, true)),
+                }
+            }
+            Some((lower, upper)) => {
+                let bound = |version: &str, inclusive| {
+                    let version = version.trim();
+                    (!version.is_empty()).then(|| (MavenVersion::parse(version), inclusive))
+                };
+                Restriction {
+                    lower: bound(lower, lower_inclusive),
+                    upper: bound(upper, upper_inclusive),
+                }
+            }
+        };
+        restrictions.push(restriction);
+
+        rest = rest[end + 1..].trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+        } else if !rest.is_empty() {
+            return Err(invalid());
+        }
+    }
+
+    Ok(RangeKind::Maven(restrictions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(range: &str, loader: ModLoader, version: &str) -> bool {
+        VersionRange::parse(range, loader).unwrap().matches(version)
+    }
+
+    #[test]
+    fn semver_predicates() {
+        let fabric = ModLoader::Fabric;
+        assert!(matches(">=0.15.0", fabric, "0.15.7"));
+        assert!(!matches(">=0.15.0", fabric, "0.14.25"));
+        assert!(matches(">=1.0.0 <2", fabric, "1.9.9"));
+        assert!(!matches(">=1.0.0 <2", fabric, "2.0.0"));
+        assert!(matches("^1.2", fabric, "1.9.0"));
+        assert!(!matches("^1.2", fabric, "2.0.0"));
+        assert!(matches("~1.2.3", fabric, "1.2.9"));
+        assert!(!matches("~1.2.3", fabric, "1.3.0"));
+        assert!(matches("1.20.x", fabric, "1.20.4"));
+        assert!(!matches("1.20.x", fabric, "1.21"));
+        assert!(matches("1.20.3 || 1.20.4", fabric, "1.20.4"));
+        assert!(matches("0.5.8", fabric, "0.5.8+mc1.20.4"));
+        assert!(matches(">=1.0.0-beta.2", fabric, "1.0.0-beta.10"));
+        assert!(!matches(">=1.0.0", fabric, "1.0.0-rc.1"));
+        assert!(matches("23w51b", fabric, "23w51b"));
+        assert!(matches("*", fabric, "23w51b"));
+        assert!(VersionRange::parse(">=23w51b", fabric).is_err());
+    }
+
+    #[test]
+    fn maven_ranges() {
+        let forge = ModLoader::Forge;
+        assert!(matches("[49.0.3,)", forge, "49.0.3"));
+        assert!(!matches("[49.0.3,)", forge, "49.0.2"));
+        assert!(matches("[1.20.4,1.21)", forge, "1.20.4"));
+        assert!(!matches("[1.20.4,1.21)", forge, "1.21"));
+        assert!(matches("[1.0,2.0)", forge, "2.0-alpha1"));
+        assert!(matches("(,1.0],[1.2,)", forge, "1.3"));
+        assert!(!matches("(,1.0],[1.2,)", forge, "1.1"));
+        assert!(matches("[1.0]", forge, "1.0.0"));
+        assert!(matches("1.0", forge, "5.0"));
+        assert!(MavenVersion::parse("1.0-beta") < MavenVersion::parse("1.0"));
+        assert!(MavenVersion::parse("1.20.4-49.0.3") > MavenVersion::parse("1.20.4"));
+        assert!(VersionRange::parse("[1.0", forge).is_err());
+        assert!(VersionRange::parse("(1.0)", forge).is_err());
+    }
+}
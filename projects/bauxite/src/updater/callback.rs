@@ -0,0 +1,162 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use bauxite_download::{FileCallback, FileStep};
+use tracing::info;
+
+pub use bauxite_download::FileStep as UpdateFileStep;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateStep {
+    AssetsIndex,
+    Libraries,
+    Client,
+    Natives,
+    Assets,
+    Done,
+}
+
+/// The files and bytes downloaded (or already up to date) so far, out of the totals of the
+/// update.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpdateProgress {
+    pub files: u64,
+    pub total_files: u64,
+    pub bytes: u64,
+    pub total_bytes: u64,
+}
+
+pub trait UpdateCallback: Sync + Send {
+    fn on_start(&self);
+    fn on_step(&self, step: UpdateStep);
+    /// Called once the totals are known, after the assets index is downloaded.
+    fn on_total(&self, total: &UpdateProgress);
+    fn on_file_step(&self, path: &Path, step: UpdateFileStep);
+    fn on_file_downloaded(&self, path: &Path, progress: &UpdateProgress);
+    fn on_finish(&self);
+}
+
+#[derive(Debug, Default)]
+pub struct DefaultUpdateCallback;
+
+impl UpdateCallback for DefaultUpdateCallback {
+    fn on_start(&self) {
+        info!("Update started");
+    }
+
+    fn on_step(&self, step: UpdateStep) {
+        info!("Step: {:?}", step);
+    }
+
+    fn on_total(&self, total: &UpdateProgress) {
+        info!(
+            "Total: {} files, {} bytes",
+            total.total_files, total.total_bytes
+        );
+    }
+
+    fn on_file_step(&self, _path: &Path, _step: UpdateFileStep) {}
+
+    fn on_file_downloaded(&self, path: &Path, progress: &UpdateProgress) {
+        info!(
+            "Downloaded: {} / {} files ({} / {} bytes), last file: {:?}",
+            progress.files, progress.total_files, progress.bytes, progress.total_bytes, path
+        );
+    }
+
+    fn on_finish(&self) {
+        info!("Update finished");
+    }
+}
+
+/// Aggregates the files downloaded by the concurrent tasks of an update.
+pub(crate) struct ProgressTracker {
+    callback: Arc<dyn UpdateCallback>,
+    total: UpdateProgress,
+    files: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl ProgressTracker {
+    pub fn new(callback: Arc<dyn UpdateCallback>, total: UpdateProgress) -> Self {
+        callback.on_total(&total);
+
+        ProgressTracker {
+            callback,
+            total,
+            files: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a downloaded file and report the progress.
+    pub fn file_downloaded(&self, path: &Path, size: u64) {
+        let progress = UpdateProgress {
+            files: self.files.fetch_add(1, Ordering::SeqCst) + 1,
+            bytes: self.bytes.fetch_add(size, Ordering::SeqCst) + size,
+            ..self.total
+        };
+
+        self.callback.on_file_downloaded(path, &progress);
+    }
+}
+
+impl FileCallback for ProgressTracker {
+    fn on_file_step(&self, path: &Path, step: FileStep) {
+        self.callback.on_file_step(path, step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<UpdateProgress>>);
+
+    impl UpdateCallback for Recorder {
+        fn on_start(&self) {}
+        fn on_step(&self, _step: UpdateStep) {}
+        fn on_total(&self, total: &UpdateProgress) {
+            self.0.lock().unwrap().push(*total);
+        }
+        fn on_file_step(&self, _path: &Path, _step: UpdateFileStep) {}
+        fn on_file_downloaded(&self, _path: &Path, progress: &UpdateProgress) {
+            self.0.lock().unwrap().push(*progress);
+        }
+        fn on_finish(&self) {}
+    }
+
+    #[test]
+    fn aggregate_progress() {
+        let recorder = Arc::new(Recorder::default());
+        let total = UpdateProgress {
+            total_files: 2,
+            total_bytes: 30,
+            ..Default::default()
+        };
+
+        let tracker = ProgressTracker::new(recorder.clone(), total);
+        tracker.file_downloaded(Path::new("a"), 10);
+        tracker.file_downloaded(Path::new("b"), 20);
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events[0], total);
+        assert_eq!(
+            events[2],
+            UpdateProgress {
+                files: 2,
+                total_files: 2,
+                bytes: 30,
+                total_bytes: 30
+            }
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod callback;
+#[allow(clippy::module_inception)]
 mod updater;
 pub use updater::Updater;
 
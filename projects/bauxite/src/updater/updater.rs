@@ -1,24 +1,67 @@
+use std::sync::Arc;
+
+use bauxite_download::Downloader;
 use tracing::debug;
 
-use crate::{instance::Instance, updater::vanilla::update_vanilla};
+use crate::{instance::Instance, minecraft::rules::RuleContext, updater::vanilla::update_vanilla};
 
-use super::vanilla::VanillaUpdateError;
+use super::{
+    callback::{DefaultUpdateCallback, UpdateCallback},
+    vanilla::VanillaUpdateError,
+};
 
-pub struct Updater {
-    instance: Instance,
+pub struct Updater<'a> {
+    instance: &'a Instance,
+    rules: RuleContext,
+    callback: Arc<dyn UpdateCallback>,
+    downloader: Downloader,
 }
 
-impl Updater {
-    pub fn new(instance: Instance) -> Self {
-        Updater { instance }
+impl<'a> Updater<'a> {
+    pub fn new(instance: &'a Instance) -> Self {
+        Updater {
+            instance,
+            rules: RuleContext::current(),
+            callback: Arc::new(DefaultUpdateCallback),
+            downloader: Downloader::shared().clone(),
+        }
+    }
+
+    /// Set the context used to check the libraries rules (defaults to the current system).
+    pub fn with_rule_context(mut self, rules: RuleContext) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the callback notified of the progress of the update.
+    pub fn with_callback(mut self, callback: Arc<dyn UpdateCallback>) -> Self {
+        self.callback = callback;
+        self
+    }
+
+    /// Set the downloader used for the files (defaults to [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
     }
 
     pub async fn update(&self) -> Result<(), UpdaterError> {
         debug!("Updating instance: {:?}", self.instance.mc_version().name());
         // TODO: clean unwanted files
+        self.callback.on_start();
 
         // TODO: download Minecraft files
-        update_vanilla(self.instance.mc_version(), self.instance.output_dir()).await?;
+        update_vanilla(
+            self.instance.mc_version(),
+            self.instance.layout(),
+            self.instance.output_dir(),
+            &self.rules,
+            &self.downloader,
+            self.callback.clone(),
+        )
+        .await?;
+
+        self.callback.on_finish();
 
         Ok(())
     }
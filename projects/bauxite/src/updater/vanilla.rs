@@ -1,63 +1,137 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
+use bauxite_download::{DownloadError, DownloadInfo, Downloader};
 use tokio::task::JoinSet;
 use tracing::debug;
 
 use crate::{
-    constants,
+    layout::Layout,
     minecraft::{
+        assets::{legacy_assets_dir, migrate_objects, object_path, reconstruct_assets},
         jsons::{
             assets_index::{AssetObject, AssetsList, AssetsListError},
             version_manifest::{ArtifactFile, Library, McVersionManifest},
         },
-        minecraft_folder,
+        natives::{extract_natives, native_libraries, NativesError},
+        rules::RuleContext,
         version::MinecraftVersion,
     },
-    utils::{
-        download::{retry_download, DownloadError, DownloadInfo},
-        os::OperatingSystem,
-    },
 };
 
+use super::callback::{ProgressTracker, UpdateCallback, UpdateProgress, UpdateStep};
+
 /// Update the vanilla Minecraft files.
+///
+/// The libraries, assets and versions are stored in the shared `layout`, while the game
+/// directory only belongs to the instance.
+///
+/// The assets index is downloaded first, so the totals reported to the `callback` include the
+/// assets.
 pub async fn update_vanilla(
     version: &dyn MinecraftVersion,
-    output: impl AsRef<Path>,
+    layout: &Layout,
+    game_dir: impl AsRef<Path>,
+    rules: &RuleContext,
+    downloader: &Downloader,
+    callback: Arc<dyn UpdateCallback>,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Updating vanilla Minecraft version: {}", version.name());
 
     let manifest = version.manifest();
 
-    let minecraft_folder = minecraft_folder();
-
-    if !minecraft_folder.exists() {
+    if !layout.root().exists() {
         debug!(
-            "Minecraft folder does not exist, creating it at {:?}",
-            minecraft_folder
+            "Cache folder does not exist, creating it at {:?}",
+            layout.root()
         );
-        std::fs::create_dir_all(&minecraft_folder).expect("Failed to create Minecraft folder");
+        std::fs::create_dir_all(layout.root())?;
     } else {
-        debug!("Found minecraft folder at {:?}", minecraft_folder);
+        debug!("Found cache folder at {:?}", layout.root());
     }
 
-    // Download libraries
-    download_libraries(manifest, &minecraft_folder).await?;
+    let game_dir = game_dir.as_ref();
+    if !game_dir.exists() {
+        debug!("Game folder does not exist, creating it at {:?}", game_dir);
+        std::fs::create_dir_all(game_dir)?;
+    }
+
+    // Persist the version JSONs (and the ones they inherit from) next to the client jar
+    let mut current = Some(version);
+    while let Some(version) = current {
+        if let Some(json) = version.raw_json() {
+            write_version_json(&version.manifest().id, json, layout)?;
+        }
+        current = version.parent();
+    }
 
     // Download assets index
-    download_assets_index(manifest, &minecraft_folder).await?;
+    callback.on_step(UpdateStep::AssetsIndex);
+    download_assets_index(manifest, layout, downloader, callback.as_ref()).await?;
+
+    let assets_index = AssetsList::from_file(layout.assets_index(&manifest.asset_index.id))?;
+    let tracker = Arc::new(ProgressTracker::new(
+        callback.clone(),
+        update_total(manifest, rules, &assets_index),
+    ));
+
+    // Download libraries
+    callback.on_step(UpdateStep::Libraries);
+    download_libraries(manifest, rules, layout, downloader, &tracker).await?;
 
     // Download client
-    download_client(manifest, &minecraft_folder).await?;
+    callback.on_step(UpdateStep::Client);
+    download_client(manifest, layout, downloader, &tracker).await?;
 
     // Download natives
-    download_native_libraries(manifest, &minecraft_folder).await?;
+    callback.on_step(UpdateStep::Natives);
+    download_native_libraries(manifest, rules, layout, downloader, &tracker).await?;
 
     // Download assets
-    download_assets(manifest, &minecraft_folder).await?;
+    callback.on_step(UpdateStep::Assets);
+    download_assets(assets_index, layout, downloader, &tracker).await?;
+
+    // Copy the assets of the legacy indexes by name
+    copy_legacy_assets(manifest, layout, game_dir).await?;
+
+    callback.on_step(UpdateStep::Done);
 
     Ok(())
 }
 
+/// The files downloaded after the assets index, and their sizes.
+fn update_total(
+    manifest: &McVersionManifest,
+    rules: &RuleContext,
+    assets_index: &AssetsList,
+) -> UpdateProgress {
+    let libraries = manifest
+        .libraries
+        .iter()
+        .filter(|library| rules.allows(&library.rules))
+        .filter_map(downloaded_artifact)
+        .map(|artifact| artifact.artifact.size);
+    let natives = native_libraries(manifest, rules)
+        .into_iter()
+        .filter(|native| native.legacy)
+        .map(|native| native.artifact.artifact.size);
+    let assets = assets_index
+        .unique_objects()
+        .into_iter()
+        .map(|object| object.size);
+
+    let sizes: Vec<u64> = libraries
+        .chain(std::iter::once(manifest.downloads.client.size))
+        .chain(natives)
+        .chain(assets)
+        .collect();
+
+    UpdateProgress {
+        total_files: sizes.len() as u64,
+        total_bytes: sizes.iter().sum(),
+        ..Default::default()
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum VanillaUpdateError {
     #[error("Failed to download the libraries")]
@@ -74,15 +148,51 @@ pub enum VanillaUpdateError {
 
     #[error("Failed to download the asset")]
     PoolError(#[from] tokio::task::JoinError),
+
+    #[error("Failed to extract the native libraries")]
+    ExtractNatives(#[from] NativesError),
+
+    #[error("Failed to migrate the assets to the hash-addressed layout")]
+    MigrateAssets(#[source] std::io::Error),
+
+    #[error("Failed to copy the legacy assets")]
+    CopyAssets(#[source] std::io::Error),
+}
+
+fn write_version_json(id: &str, json: &str, layout: &Layout) -> Result<(), VanillaUpdateError> {
+    let path = layout.version_json(id);
+
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == json) {
+        debug!("Version JSON is up to date: {:?}", path);
+        return Ok(());
+    }
+
+    debug!("Writing version JSON to {:?}", path);
+    std::fs::create_dir_all(layout.version_dir(id))?;
+    std::fs::write(path, json)?;
+
+    Ok(())
+}
+
+/// The artifact of a library downloaded by the updater.
+///
+/// The artifacts without URL are generated by the loaders installers (Forge processors...).
+fn downloaded_artifact(library: &Library) -> Option<ArtifactFile> {
+    library
+        .artifact()
+        .filter(|artifact| !artifact.artifact.url.is_empty())
 }
 
 async fn download_libraries(
     manifest: &McVersionManifest,
-    minecraft_folder: impl AsRef<Path>,
+    rules: &RuleContext,
+    layout: &Layout,
+    downloader: &Downloader,
+    tracker: &Arc<ProgressTracker>,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Downloading libraries");
 
-    let libraries_folder = minecraft_folder.as_ref().join("libraries");
+    let libraries_folder = layout.libraries_dir();
 
     if !libraries_folder.exists() {
         debug!(
@@ -99,17 +209,17 @@ async fn download_libraries(
     for library in &manifest.libraries {
         debug!("Downloading library: {}", library.name);
 
-        if library.downloads.artifact.is_none()
-            || (!library.rules.is_empty() && !check_libs_rules(&library))
-        {
+        if downloaded_artifact(library).is_none() || !rules.allows(&library.rules) {
             continue;
         }
 
         let library = library.clone();
         let libraries_folder = libraries_folder.clone();
+        let downloader = downloader.clone();
+        let tracker = tracker.clone();
         joinset.spawn(async move {
             // Download library
-            download_library(library, libraries_folder).await
+            download_library(library, libraries_folder, &downloader, &tracker).await
         });
     }
 
@@ -121,7 +231,7 @@ async fn download_libraries(
                 }
                 Err(e) => {
                     debug!("Error downloading library: {:?}", e);
-                    return Err(e.into());
+                    return Err(e);
                 }
             },
             Err(e) => {
@@ -137,11 +247,13 @@ async fn download_libraries(
 async fn download_library(
     library: Library,
     libraries_folder: impl AsRef<Path>,
+    downloader: &Downloader,
+    tracker: &ProgressTracker,
 ) -> Result<String, VanillaUpdateError> {
     debug!("Downloading library: {}", library.name);
 
     // Get the artifact file information
-    let file = match library.downloads.artifact {
+    let file = match downloaded_artifact(&library) {
         Some(file) => file,
         None => return Err(VanillaUpdateError::NoArtifact),
     };
@@ -149,26 +261,33 @@ async fn download_library(
     let lib_path = libraries_folder.as_ref().join(file.path);
 
     // Download the library
-    retry_download(DownloadInfo {
-        path: lib_path,
-        url: file.artifact.url,
-        sha1: file.artifact.sha1,
-        size: file.artifact.size,
-    })
-    .await?;
+    let path = downloader
+        .download(
+            DownloadInfo {
+                path: lib_path,
+                url: file.artifact.url,
+                sha1: file.artifact.sha1,
+                size: file.artifact.size,
+            },
+            tracker,
+        )
+        .await?;
+    tracker.file_downloaded(&path, file.artifact.size);
 
     Ok(library.name)
 }
 
 async fn download_assets_index(
     manifest: &McVersionManifest,
-    minecraft_folder: impl AsRef<Path>,
+    layout: &Layout,
+    downloader: &Downloader,
+    callback: &dyn UpdateCallback,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Downloading assets index");
 
     let assets_index = manifest.asset_index.clone();
 
-    let assets_folder = minecraft_folder.as_ref().join("assets");
+    let assets_folder = layout.assets_dir();
 
     if !assets_folder.exists() {
         debug!(
@@ -180,34 +299,36 @@ async fn download_assets_index(
         debug!("Found assets folder at {:?}", assets_folder);
     }
 
-    let assets_index_path = assets_folder
-        .join("indexes")
-        .join(format!("{}.json", assets_index.id));
+    let assets_index_path = layout.assets_index(&assets_index.id);
 
     // Download the assets index
-    retry_download(DownloadInfo {
-        path: assets_index_path,
-        url: assets_index.url,
-        sha1: assets_index.sha1,
-        size: assets_index.size,
-    })
-    .await?;
+    downloader
+        .download(
+            DownloadInfo {
+                path: assets_index_path,
+                url: assets_index.url,
+                sha1: Some(assets_index.sha1),
+                size: assets_index.size,
+            },
+            &|path: &Path, step| callback.on_file_step(path, step),
+        )
+        .await?;
 
     Ok(())
 }
 
 async fn download_client(
     manifest: &McVersionManifest,
-    minecraft_folder: impl AsRef<Path>,
+    layout: &Layout,
+    downloader: &Downloader,
+    tracker: &ProgressTracker,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Downloading client");
 
     let client = manifest.downloads.client.clone();
 
-    let client_folder = minecraft_folder
-        .as_ref()
-        .join("versions")
-        .join(&manifest.id);
+    // Inherited versions use the jar of their parent
+    let client_folder = layout.version_dir(manifest.jar());
 
     if !client_folder.exists() {
         debug!(
@@ -220,64 +341,48 @@ async fn download_client(
     }
 
     // Download the client.jar
-    let client_path = client_folder.join(format!("{}.jar", manifest.id));
-
-    retry_download(DownloadInfo {
-        path: client_path,
-        sha1: client.sha1,
-        size: client.size,
-        url: client.url,
-    })
-    .await?;
+    let client_path = layout.client_jar(manifest.jar());
+
+    let path = downloader
+        .download(
+            DownloadInfo {
+                path: client_path,
+                sha1: client.sha1,
+                size: client.size,
+                url: client.url,
+            },
+            tracker,
+        )
+        .await?;
+    tracker.file_downloaded(&path, client.size);
 
     Ok(())
 }
 
 async fn download_native_libraries(
     manifest: &McVersionManifest,
-    minecraft_folder: impl AsRef<Path>,
+    rules: &RuleContext,
+    layout: &Layout,
+    downloader: &Downloader,
+    tracker: &Arc<ProgressTracker>,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Downloading native libraries");
 
-    let natives_folder = minecraft_folder.as_ref().join("natives");
-
-    if !natives_folder.exists() {
-        debug!(
-            "Natives folder does not exist, creating it at {:?}",
-            natives_folder
-        );
-        std::fs::create_dir_all(&natives_folder).expect("Failed to create natives folder");
-    } else {
-        debug!("Found natives folder at {:?}", natives_folder);
-    }
-
-    let os = OperatingSystem::current();
+    let libraries_folder = layout.libraries_dir();
     let mut joinset = JoinSet::new();
 
-    for lib in &manifest.libraries {
-        if lib.natives.is_empty() {
-            continue;
-        }
-
-        let classifiers = match &lib.downloads.classifiers {
-            Some(classifiers) => classifiers.clone(),
-            None => continue,
-        };
-
-        let natives_map = match lib.natives.get(&os.name()) {
-            Some(map) => map,
-            None => continue,
-        };
-
-        let classifier = match classifiers.get(natives_map) {
-            Some(classifier) => classifier.clone(),
-            None => continue,
-        };
-
-        let natives_folder = natives_folder.clone();
+    // Natives shipped as regular artifacts were downloaded with the libraries
+    for native in native_libraries(manifest, rules)
+        .into_iter()
+        .filter(|n| n.legacy)
+    {
+        let artifact = native.artifact.clone();
+        let libraries_folder = libraries_folder.clone();
+        let downloader = downloader.clone();
+        let tracker = tracker.clone();
         joinset.spawn(async move {
             // Download native library
-            download_native(classifier, natives_folder).await
+            download_native(artifact, libraries_folder, &downloader, &tracker).await
         });
     }
 
@@ -289,7 +394,7 @@ async fn download_native_libraries(
                 }
                 Err(e) => {
                     debug!("Error downloading native library: {:?}", e);
-                    return Err(e.into());
+                    return Err(e);
                 }
             },
             Err(e) => {
@@ -299,73 +404,55 @@ async fn download_native_libraries(
         }
     }
 
+    // Extract the natives in the version folder
+    let natives_folder = layout.natives_dir(&manifest.id);
+
+    let manifest = manifest.clone();
+    let rules = rules.clone();
+    tokio::task::spawn_blocking(move || {
+        extract_natives(&manifest, &rules, libraries_folder, natives_folder)
+    })
+    .await??;
+
     Ok(())
 }
 
 async fn download_native(
-    classifier: ArtifactFile,
-    natives_folder: impl AsRef<Path>,
+    artifact: ArtifactFile,
+    libraries_folder: impl AsRef<Path>,
+    downloader: &Downloader,
+    tracker: &ProgressTracker,
 ) -> Result<String, VanillaUpdateError> {
     debug!("Downloading native library");
 
-    let file_name = classifier.path.split('/').last().unwrap();
-    let native_path = natives_folder.as_ref().join(&file_name);
+    let native_path = libraries_folder.as_ref().join(&artifact.path);
 
     // Download the native library
-    retry_download(DownloadInfo {
-        path: native_path,
-        sha1: classifier.artifact.sha1,
-        size: classifier.artifact.size,
-        url: classifier.artifact.url,
-    })
-    .await?;
-
-    Ok(classifier.path)
-}
-
-fn check_libs_rules(library: &Library) -> bool {
-    let os = OperatingSystem::current();
-    let mut allowed = false;
-
-    for rule in &library.rules {
-        let action: bool = rule.action.clone().into();
-
-        for (name, value) in &rule.os {
-            match name.as_str() {
-                "name" => {
-                    if value != os.name().as_ref() {
-                        allowed = action;
-                    }
-                }
-                "arch" => {
-                    if value != os.arch() {
-                        allowed = action;
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
+    let path = downloader
+        .download(
+            DownloadInfo {
+                path: native_path,
+                sha1: artifact.artifact.sha1,
+                size: artifact.artifact.size,
+                url: artifact.artifact.url,
+            },
+            tracker,
+        )
+        .await?;
+    tracker.file_downloaded(&path, artifact.artifact.size);
 
-    allowed
+    Ok(artifact.path)
 }
 
 async fn download_assets(
-    manifest: &McVersionManifest,
-    minecraft_folder: impl AsRef<Path>,
+    assets_index: AssetsList,
+    layout: &Layout,
+    downloader: &Downloader,
+    tracker: &Arc<ProgressTracker>,
 ) -> Result<(), VanillaUpdateError> {
     debug!("Downloading assets");
 
-    // Read assets from assets index
-    let assets_index_path = minecraft_folder
-        .as_ref()
-        .join("assets")
-        .join("indexes")
-        .join(format!("{}.json", manifest.asset_index.id));
-
-    let assets_index = AssetsList::from_file(&assets_index_path)?;
-
-    let assets_folder = minecraft_folder.as_ref().join("assets");
+    let assets_folder = layout.assets_dir();
 
     if !assets_folder.exists() {
         debug!(
@@ -377,15 +464,27 @@ async fn download_assets(
         debug!("Found assets folder at {:?}", assets_folder);
     }
 
+    // Move the assets downloaded by name to their hash-addressed path
+    let assets_index = {
+        let assets_folder = assets_folder.clone();
+        tokio::task::spawn_blocking(move || {
+            migrate_objects(&assets_index, assets_folder).map(|_| assets_index)
+        })
+        .await?
+        .map_err(VanillaUpdateError::MigrateAssets)?
+    };
+
     let mut joinset = JoinSet::new();
 
-    for (name, asset) in &assets_index.objects {
-        let name = name.clone();
+    // Several assets can share the same object
+    for asset in assets_index.unique_objects() {
         let asset = asset.clone();
         let assets_folder = assets_folder.clone();
+        let downloader = downloader.clone();
+        let tracker = tracker.clone();
         joinset.spawn(async move {
             // Download asset
-            download_asset(&name, asset, assets_folder).await
+            download_asset(asset, assets_folder, &downloader, &tracker).await
         });
     }
 
@@ -397,7 +496,7 @@ async fn download_assets(
                 }
                 Err(e) => {
                     debug!("Error downloading asset: {:?}", e);
-                    return Err(e.into());
+                    return Err(e);
                 }
             },
             Err(e) => {
@@ -411,13 +510,14 @@ async fn download_assets(
 }
 
 async fn download_asset(
-    name: &str,
     asset_object: AssetObject,
-    minecraft_folder: impl AsRef<Path>,
+    assets_folder: impl AsRef<Path>,
+    downloader: &Downloader,
+    tracker: &ProgressTracker,
 ) -> Result<(), VanillaUpdateError> {
-    debug!("Downloading asset: {}", name);
+    debug!("Downloading asset: {}", asset_object.hash);
 
-    let asset_folder = minecraft_folder.as_ref().join("objects");
+    let asset_folder = assets_folder.as_ref().join("objects");
 
     if !asset_folder.exists() {
         debug!(
@@ -429,21 +529,50 @@ async fn download_asset(
         debug!("Found objects folder at {:?}", asset_folder);
     }
 
-    let asset_path = asset_folder.join(&name);
+    let asset_path = object_path(assets_folder, &asset_object);
 
     // Download the asset
-    retry_download(DownloadInfo {
-        path: asset_path,
-        sha1: asset_object.hash.to_string(),
-        size: asset_object.size,
-        url: format!(
-            "{}/{}/{}",
-            constants::RESOURCES_BASE,
-            &asset_object.hash[..2],
-            &asset_object.hash
-        ),
+    let path = downloader
+        .download(
+            DownloadInfo {
+                path: asset_path,
+                sha1: Some(asset_object.hash.to_string()),
+                size: asset_object.size,
+                url: format!(
+                    "{}/{}/{}",
+                    downloader.endpoints().resources(),
+                    &asset_object.hash[..2],
+                    &asset_object.hash
+                ),
+            },
+            tracker,
+        )
+        .await?;
+    tracker.file_downloaded(&path, asset_object.size);
+
+    Ok(())
+}
+
+async fn copy_legacy_assets(
+    manifest: &McVersionManifest,
+    layout: &Layout,
+    game_dir: &Path,
+) -> Result<(), VanillaUpdateError> {
+    let index_id = &manifest.asset_index.id;
+    let assets_index = AssetsList::from_file(layout.assets_index(index_id))?;
+
+    let Some(target_dir) = legacy_assets_dir(&assets_index, index_id, layout, game_dir) else {
+        return Ok(());
+    };
+
+    debug!("Copying legacy assets to {:?}", target_dir);
+
+    let assets_folder = layout.assets_dir();
+    tokio::task::spawn_blocking(move || {
+        reconstruct_assets(&assets_index, assets_folder, target_dir)
     })
-    .await?;
+    .await?
+    .map_err(VanillaUpdateError::CopyAssets)?;
 
     Ok(())
 }
@@ -44,34 +44,54 @@ impl AsRef<str> for OsName {
     }
 }
 
-#[derive(Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
-pub struct OperatingSystem {
-    name: OsName,
-    arch: String,
+/// The architecture of the current system, as named in the Mojang rules.
+pub fn current_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86" => "x86",
+        "x86_64" => "x86_64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        arch => arch,
+    }
 }
 
-impl OperatingSystem {
-    pub fn current() -> Self {
-        OperatingSystem {
-            name: OsName::current(),
-            arch: std::env::consts::ARCH.to_string(),
-        }
+/// The version of the current system (e.g. `10.0.19045` on Windows, `6.1.0` on Linux).
+/// An empty string is returned when the version can not be detected.
+pub fn current_version() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|version| version.trim().to_string())
+            .unwrap_or_default()
     }
 
-    pub fn name(&self) -> OsName {
-        self.name.clone()
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_default()
     }
 
-    pub fn name_str(&self) -> &str {
-        match self.name {
-            OsName::MacOs => "osx",
-            OsName::Linux => "linux",
-            OsName::Windows => "windows",
-            OsName::Unknown => "unknown",
-        }
+    #[cfg(target_os = "windows")]
+    {
+        // "Microsoft Windows [Version 10.0.19045.3693]"
+        std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .output()
+            .ok()
+            .and_then(|output| {
+                let output = String::from_utf8_lossy(&output.stdout).to_string();
+                let start = output.find("Version ")? + "Version ".len();
+                let end = output[start..].find(']')? + start;
+                Some(output[start..end].to_string())
+            })
+            .unwrap_or_default()
     }
 
-    pub fn arch(&self) -> &str {
-        &self.arch
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        String::new()
     }
 }
@@ -1,6 +1,33 @@
-use serde::forward_to_deserialize_any;
+use serde::{forward_to_deserialize_any, Deserialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tracing::trace;
 
+/// Deserializes an optional RFC 3339 date, also accepting the offsets without a colon
+/// (`+0000`) used by the loaders JSONs.
+pub fn deserialize_optional_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<OffsetDateTime>, D::Error>
+where
+    D: ::serde::Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    let parsed = OffsetDateTime::parse(&value, &Rfc3339).or_else(|e| {
+        // Insert the colon in the offset and try again
+        match value.len().checked_sub(2) {
+            Some(split) if value.is_char_boundary(split) => {
+                let (datetime, minutes) = value.split_at(split);
+                OffsetDateTime::parse(&format!("{}:{}", datetime, minutes), &Rfc3339)
+            }
+            _ => Err(e),
+        }
+    });
+
+    parsed.map(Some).map_err(::serde::de::Error::custom)
+}
+
 /// Deserializes a string or a sequence of strings into a vector of the target type.
 pub fn deserialize_string_or_seq_string<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
 where
@@ -0,0 +1,215 @@
+//! Helpers shared by the tests serving the Mojang and loaders APIs from a local server.
+
+#![allow(dead_code)]
+
+use std::{io::Write, ops::Deref};
+
+use bauxite::{
+    minecraft::{rules::RuleContext, version::MinecraftVersion},
+    Downloader, DownloaderBuilder, Endpoints, Instance, InstanceBuilder, Updater,
+};
+use mockito::{Mock, Server};
+use sha1::{Digest, Sha1};
+use sha2::Sha512;
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+pub fn sha1(content: &[u8]) -> String {
+    hex::encode(Sha1::digest(content))
+}
+
+pub fn sha512(content: &[u8]) -> String {
+    hex::encode(Sha512::digest(content))
+}
+
+/// Build a zip archive from its entries.
+pub fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+pub async fn serve(server: &mut Server, path: &str, body: &[u8]) -> Mock {
+    server
+        .mock("GET", path)
+        .with_body(body)
+        .expect_at_least(1)
+        .create_async()
+        .await
+}
+
+/// A downloader fetching everything from the local server.
+pub fn mirror_downloader(server: &Server) -> Downloader {
+    DownloaderBuilder::new()
+        .with_endpoints(
+            Endpoints::new()
+                .with_mirror(&server.url())
+                .with_fabric_meta(format!("{}/fabric-meta", server.url()))
+                .with_quilt_meta(format!("{}/quilt-meta", server.url()))
+                .with_modrinth_api(format!("{}/modrinth", server.url()))
+                .with_curseforge_api(format!("{}/curseforge", server.url()))
+                .with_rewrite(
+                    "https://maven.minecraftforge.net",
+                    format!("{}/forge-maven", server.url()),
+                )
+                .with_rewrite(
+                    "https://maven.neoforged.net/releases",
+                    format!("{}/neoforge-maven", server.url()),
+                )
+                .with_rewrite(
+                    "https://repo1.maven.org/maven2",
+                    format!("{}/central", server.url()),
+                ),
+        )
+        .build()
+}
+
+/// Serve a modern vanilla version without libraries nor assets, and its client jar.
+pub async fn serve_vanilla(server: &mut Server, id: &str) -> Vec<Mock> {
+    let client = b"client";
+    let assets_index = r#"{"objects": {}}"#;
+
+    let version = serde_json::json!({
+        "arguments": {
+            "game": ["--username", "${auth_player_name}"],
+            "jvm": ["-cp", "${classpath}"]
+        },
+        "assetIndex": {
+            "id": "12",
+            "sha1": sha1(assets_index.as_bytes()),
+            "size": assets_index.len(),
+            "totalSize": 0,
+            "url": "https://piston-meta.mojang.com/v1/packages/12.json"
+        },
+        "assets": "12",
+        "downloads": {
+            "client": {
+                "sha1": sha1(client),
+                "size": client.len(),
+                "url": format!("https://piston-data.mojang.com/v1/objects/{}.jar", id)
+            }
+        },
+        "id": id,
+        "libraries": [],
+        "mainClass": "net.minecraft.client.main.Main",
+        "minimumLauncherVersion": 21,
+        "releaseTime": "2023-12-07T12:56:20+00:00",
+        "time": "2023-12-07T12:56:20+00:00",
+        "type": "release"
+    })
+    .to_string();
+
+    let versions = serde_json::json!({
+        "latest": { "release": id, "snapshot": id },
+        "versions": [{
+            "id": id,
+            "type": "release",
+            "url": format!("https://piston-meta.mojang.com/v1/packages/{}.json", id),
+            "sha1": sha1(version.as_bytes()),
+            "time": "2023-12-07T12:56:20+00:00",
+            "releaseTime": "2023-12-07T12:56:20+00:00"
+        }]
+    })
+    .to_string();
+
+    vec![
+        serve(
+            server,
+            "/mc/game/version_manifest_v2.json",
+            versions.as_bytes(),
+        )
+        .await,
+        serve(
+            server,
+            &format!("/v1/packages/{}.json", id),
+            version.as_bytes(),
+        )
+        .await,
+        serve(server, "/v1/packages/12.json", assets_index.as_bytes()).await,
+        serve(server, &format!("/v1/objects/{}.jar", id), client).await,
+    ]
+}
+
+/// Serve the loader versions of a game version and the profile of one of them from the local
+/// stand-in for a meta API (`/fabric-meta` or `/quilt-meta`).
+pub async fn serve_meta(
+    server: &mut Server,
+    meta: &str,
+    game_version: &str,
+    loader_version: &str,
+    loaders: &str,
+    profile: &str,
+) -> Vec<Mock> {
+    vec![
+        serve(
+            server,
+            &format!("/{}/versions/loader/{}", meta, game_version),
+            loaders.as_bytes(),
+        )
+        .await,
+        serve(
+            server,
+            &format!(
+                "/{}/versions/loader/{}/{}/profile/json",
+                meta, game_version, loader_version
+            ),
+            profile.as_bytes(),
+        )
+        .await,
+    ]
+}
+
+/// An instance in a unique temporary folder, removed when dropped.
+pub struct TestInstance {
+    instance: Instance,
+    root: TempDir,
+}
+
+impl TestInstance {
+    pub fn new<V: MinecraftVersion + 'static>(version: V) -> Self {
+        let root = tempfile::Builder::new()
+            .prefix("bauxite-test-")
+            .tempdir()
+            .unwrap();
+        let instance = InstanceBuilder::new(version)
+            .with_output_dir(root.path().join("game"))
+            .with_cache_dir(root.path().join("cache"))
+            .build();
+
+        TestInstance { instance, root }
+    }
+
+    /// Download the version with the updater, then check that every mock was requested and
+    /// that the client jar and the version JSON are in the layout.
+    pub async fn update(&self, downloader: Downloader, mocks: Vec<Mock>) {
+        Updater::new(&self.instance)
+            .with_downloader(downloader)
+            .with_rule_context(RuleContext::current())
+            .update()
+            .await
+            .unwrap();
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+
+        let layout = self.instance.layout();
+        let version = self.instance.mc_version();
+        assert_eq!(
+            std::fs::read(layout.client_jar(version.manifest().jar())).unwrap(),
+            b"client"
+        );
+        assert!(layout.version_json(version.id()).exists());
+    }
+}
+
+impl Deref for TestInstance {
+    type Target = Instance;
+
+    fn deref(&self) -> &Instance {
+        &self.instance
+    }
+}
@@ -0,0 +1,201 @@
+//! Resolve and download mod files from a local stand-in for the CurseForge API.
+
+use bauxite::{
+    minecraft::vanilla::VanillaVersionBuilder,
+    mods::curseforge::{fingerprint, CurseForgeClient, CurseForgeError, ModLoaderType},
+    DownloadError, InstanceBuilder,
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{mirror_downloader, serve, serve_vanilla, sha1};
+
+fn file_json(
+    id: u32,
+    name: &str,
+    download_url: Option<String>,
+    content: &[u8],
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "modId": 238222,
+        "displayName": name,
+        "fileName": name,
+        "fileLength": content.len(),
+        "downloadUrl": download_url,
+        "hashes": [
+            { "value": sha1(content), "algo": 1 },
+            { "value": "d41d8cd98f00b204e9800998ecf8427e", "algo": 2 }
+        ],
+        "fileFingerprint": fingerprint(content),
+        "gameVersions": ["1.20.4", "Fabric", "Client"],
+        "dependencies": [{ "modId": 306612, "relationType": 3 }]
+    })
+}
+
+#[tokio::test]
+async fn download_mod_files() {
+    let mut server = Server::new_async().await;
+    let root = std::env::temp_dir().join("bauxite-curseforge-test");
+    let _ = std::fs::remove_dir_all(&root);
+
+    let jar = b"jei\n";
+    let allowed = file_json(
+        4973441,
+        "jei-1.20.4.jar",
+        Some(format!("{}/cdn/jei-1.20.4.jar", server.url())),
+        jar,
+    );
+    let disallowed = file_json(4973440, "jei-1.20.4-api.jar", None, b"api");
+
+    let page = |data: serde_json::Value, index: u32| {
+        serde_json::json!({
+            "data": [data],
+            "pagination": { "index": index, "pageSize": 50, "resultCount": 1, "totalCount": 2 }
+        })
+        .to_string()
+    };
+    let files_mock = |index: u32| {
+        Matcher::AllOf(vec![
+            Matcher::UrlEncoded("gameVersion".into(), "1.20.4".into()),
+            Matcher::UrlEncoded("modLoaderType".into(), "4".into()),
+            Matcher::UrlEncoded("index".into(), index.to_string()),
+        ])
+    };
+
+    serve_vanilla(&mut server, "1.20.4").await;
+    let mocks = vec![
+        server
+            .mock("GET", "/curseforge/mods/238222")
+            .match_header("x-api-key", "secret")
+            .with_body(
+                serde_json::json!({
+                    "data": {
+                        "id": 238222,
+                        "name": "Just Enough Items (JEI)",
+                        "slug": "jei",
+                        "summary": "View Items and Recipes",
+                        "links": { "websiteUrl": "https://www.curseforge.com/minecraft/mc-mods/jei" },
+                        "allowModDistribution": true
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await,
+        server
+            .mock("GET", "/curseforge/mods/238222/files")
+            .match_header("x-api-key", "secret")
+            .match_query(files_mock(0))
+            .with_body(page(allowed, 0))
+            .create_async()
+            .await,
+        server
+            .mock("GET", "/curseforge/mods/238222/files")
+            .match_header("x-api-key", "secret")
+            .match_query(files_mock(1))
+            .with_body(page(disallowed, 1))
+            .create_async()
+            .await,
+        serve(&mut server, "/cdn/jei-1.20.4.jar", jar).await,
+    ];
+
+    let downloader = mirror_downloader(&server);
+    let client = CurseForgeClient::new("secret").with_downloader(downloader.clone());
+
+    let jei = client.get_mod(238222).await.unwrap();
+    assert_eq!(jei.slug, "jei");
+
+    let files = client
+        .mod_files(jei.id, Some("1.20.4"), Some(ModLoaderType::Fabric))
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].sha1(), Some(sha1(jar).as_str()));
+
+    let vanilla = VanillaVersionBuilder::new("1.20.4")
+        .with_downloader(downloader)
+        .build()
+        .await
+        .unwrap();
+    let instance = InstanceBuilder::new(vanilla)
+        .with_output_dir(root.join("game"))
+        .with_cache_dir(root.join("cache"))
+        .build();
+
+    // A file failing to download is reported with the other ones
+    let mut invalid = files[0].clone();
+    invalid.file_name = "../jei-1.20.4.jar".to_string();
+    let files = [files, vec![invalid]].concat();
+
+    let report = client.download_files(&files, &instance).await.unwrap();
+    assert_eq!(
+        report.downloaded,
+        [instance.mods_dir().join("jei-1.20.4.jar")]
+    );
+    assert_eq!(report.disallowed.len(), 1);
+    assert_eq!(report.disallowed[0].id, 4973440);
+    assert!(
+        matches!(
+            report.failed.as_slice(),
+            [(_, CurseForgeError::InvalidFileName(name))] if name == "../jei-1.20.4.jar"
+        ),
+        "{:?}",
+        report.failed
+    );
+    assert_eq!(std::fs::read(&report.downloaded[0]).unwrap(), jar);
+
+    let result = client
+        .download_file(&report.disallowed[0], &instance.mods_dir())
+        .await;
+    assert!(matches!(
+        result,
+        Err(CurseForgeError::DownloadDisallowed {
+            mod_id: 238222,
+            file_id: 4973440
+        })
+    ));
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn reject_invalid_fingerprint() {
+    let mut server = Server::new_async().await;
+    let dir = std::env::temp_dir().join("bauxite-curseforge-fingerprint-test");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut file = file_json(
+        4973441,
+        "jei-1.20.4.jar",
+        Some(format!("{}/cdn/jei-1.20.4.jar", server.url())),
+        b"jei",
+    );
+    file["fileFingerprint"] = fingerprint(b"other").into();
+    server
+        .mock("GET", "/curseforge/mods/238222/files/4973441")
+        .with_body(serde_json::json!({ "data": file }).to_string())
+        .create_async()
+        .await;
+    serve(&mut server, "/cdn/jei-1.20.4.jar", b"jei").await;
+
+    let client = CurseForgeClient::new("secret").with_downloader(mirror_downloader(&server));
+    let file = client.get_file(238222, 4973441).await.unwrap();
+    let result = client.download_file(&file, &dir).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(CurseForgeError::DownloadError(
+                DownloadError::InvalidChecksum
+            ))
+        ),
+        "{:?}",
+        result
+    );
+    assert!(!dir.join("jei-1.20.4.jar").exists());
+}
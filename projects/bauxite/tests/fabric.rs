@@ -0,0 +1,154 @@
+//! Install a Fabric version from a local stand-in for the Fabric meta API.
+
+use bauxite::{
+    minecraft::version::MinecraftVersion,
+    modloaders::fabric::{fetch_loader_versions, FabricVersionBuilder, FabricVersionError},
+};
+use mockito::Server;
+
+mod common;
+use common::{mirror_downloader, serve, serve_meta, serve_vanilla, sha1, TestInstance};
+
+#[tokio::test]
+async fn install_fabric() {
+    let mut server = Server::new_async().await;
+
+    let loader = b"fabric-loader";
+
+    let loaders = serde_json::json!([
+        {
+            "loader": { "separator": ".", "build": 4, "maven": "net.fabricmc:fabric-loader:0.16.0-beta.1", "version": "0.16.0-beta.1", "stable": false },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true }
+        },
+        {
+            "loader": { "separator": ".", "build": 3, "maven": "net.fabricmc:fabric-loader:0.15.3", "version": "0.15.3", "stable": true },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true }
+        }
+    ])
+    .to_string();
+
+    let profile = serde_json::json!({
+        "id": "fabric-loader-0.15.3-1.20.4",
+        "inheritsFrom": "1.20.4",
+        "releaseTime": "2024-01-05T12:00:00+0000",
+        "time": "2024-01-05T12:00:00+0000",
+        "type": "release",
+        "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+        "arguments": { "game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "] },
+        "libraries": [
+            {
+                "name": "net.fabricmc:fabric-loader:0.15.3",
+                "url": format!("{}/fabric-maven/", server.url()),
+                "sha1": sha1(loader),
+                "size": loader.len()
+            }
+        ]
+    })
+    .to_string();
+
+    let mut mocks = serve_vanilla(&mut server, "1.20.4").await;
+    mocks.extend(
+        serve_meta(
+            &mut server,
+            "fabric-meta",
+            "1.20.4",
+            "0.15.3",
+            &loaders,
+            &profile,
+        )
+        .await,
+    );
+    mocks.push(
+        serve(
+            &mut server,
+            "/fabric-maven/net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar",
+            loader,
+        )
+        .await,
+    );
+
+    let downloader = mirror_downloader(&server);
+
+    let versions = fetch_loader_versions(&downloader, "1.20.4").await.unwrap();
+    assert_eq!(versions.len(), 2);
+
+    // The latest stable loader is used by default
+    let fabric = FabricVersionBuilder::new("1.20.4")
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(fabric.loader_version(), "0.15.3");
+    assert_eq!(fabric.id(), "fabric-loader-0.15.3-1.20.4");
+    assert_eq!(
+        fabric.manifest().main_class,
+        "net.fabricmc.loader.impl.launch.knot.KnotClient"
+    );
+
+    let instance = TestInstance::new(fabric);
+    instance.update(downloader.clone(), mocks).await;
+
+    let layout = instance.layout();
+    assert_eq!(
+        std::fs::read(
+            layout
+                .libraries_dir()
+                .join("net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar")
+        )
+        .unwrap(),
+        loader
+    );
+    assert_eq!(
+        std::fs::read_to_string(layout.version_json("fabric-loader-0.15.3-1.20.4")).unwrap(),
+        profile
+    );
+    assert!(layout.version_json("1.20.4").exists());
+
+    // Unknown loader versions are rejected
+    let result = FabricVersionBuilder::new("1.20.4")
+        .with_loader_version("0.1.0")
+        .with_downloader(downloader)
+        .build()
+        .await;
+    assert!(matches!(
+        result,
+        Err(FabricVersionError::LoaderVersionNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn reject_non_utf8_profile() {
+    let mut server = Server::new_async().await;
+
+    let loaders = serde_json::json!([
+        {
+            "loader": { "separator": ".", "build": 3, "maven": "net.fabricmc:fabric-loader:0.15.3", "version": "0.15.3", "stable": true },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4", "stable": true }
+        }
+    ])
+    .to_string();
+
+    serve_vanilla(&mut server, "1.20.4").await;
+    serve(
+        &mut server,
+        "/fabric-meta/versions/loader/1.20.4",
+        loaders.as_bytes(),
+    )
+    .await;
+    serve(
+        &mut server,
+        "/fabric-meta/versions/loader/1.20.4/0.15.3/profile/json",
+        b"{\"id\": \"fabric-loader-\xff\"}",
+    )
+    .await;
+
+    let result = FabricVersionBuilder::new("1.20.4")
+        .with_downloader(mirror_downloader(&server))
+        .build()
+        .await;
+    assert!(
+        matches!(result, Err(FabricVersionError::InvalidEncoding(_))),
+        "{:?}",
+        result
+    );
+}
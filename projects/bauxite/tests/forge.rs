@@ -0,0 +1,180 @@
+//! Install a Forge version from an installer served by a local stand-in for the Forge maven.
+
+use bauxite::{
+    minecraft::{vanilla::VanillaVersionBuilder, version::MinecraftVersion},
+    modloaders::forge::{ForgeInstallError, ForgeVersion, ForgeVersionBuilder},
+};
+use mockito::Server;
+
+mod common;
+use common::{mirror_downloader, serve, serve_vanilla, sha1, zip, TestInstance};
+
+#[tokio::test]
+async fn install_forge() {
+    let mut server = Server::new_async().await;
+
+    let universal = b"universal";
+    let patched = b"patched";
+    let binarypatcher = zip(&[(
+        "META-INF/MANIFEST.MF",
+        b"Manifest-Version: 1.0\r\nMain-Class: net.minecraftforge.binarypatcher.ConsoleTool\r\n",
+    )]);
+
+    let profile = serde_json::json!({
+        "spec": 1,
+        "profile": "forge",
+        "version": "1.20.4-forge-49.0.3",
+        "minecraft": "1.20.4",
+        "json": "/version.json",
+        "data": {
+            "PATCHED": {
+                "client": "[net.minecraftforge:forge:1.20.4-49.0.3:client]",
+                "server": "[net.minecraftforge:forge:1.20.4-49.0.3:server]"
+            },
+            "PATCHED_SHA": { "client": format!("'{}'", sha1(patched)), "server": "''" },
+            "BINPATCH": { "client": "/data/client.lzma", "server": "/data/server.lzma" }
+        },
+        "processors": [
+            {
+                "sides": ["server"],
+                "jar": "net.minecraftforge:installertools:1.3.0",
+                "args": ["--task", "EXTRACT_FILES"]
+            },
+            {
+                "jar": "net.minecraftforge:binarypatcher:1.1.1",
+                "classpath": [],
+                "args": ["--clean", "{MINECRAFT_JAR}", "--output", "{PATCHED}", "--apply", "{BINPATCH}"],
+                "outputs": { "{PATCHED}": "{PATCHED_SHA}" }
+            }
+        ],
+        "libraries": [
+            {
+                "name": "net.minecraftforge:binarypatcher:1.1.1",
+                "downloads": {
+                    "artifact": {
+                        "path": "net/minecraftforge/binarypatcher/1.1.1/binarypatcher-1.1.1.jar",
+                        "url": "https://maven.minecraftforge.net/net/minecraftforge/binarypatcher/1.1.1/binarypatcher-1.1.1.jar",
+                        "sha1": sha1(&binarypatcher),
+                        "size": binarypatcher.len()
+                    }
+                }
+            }
+        ]
+    })
+    .to_string();
+
+    let version = serde_json::json!({
+        "id": "1.20.4-forge-49.0.3",
+        "inheritsFrom": "1.20.4",
+        "type": "release",
+        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+        "arguments": { "game": ["--launchTarget", "forgeclient"] },
+        "libraries": [
+            {
+                "name": "net.minecraftforge:forge:1.20.4-49.0.3:universal",
+                "downloads": {
+                    "artifact": {
+                        "path": "net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-universal.jar",
+                        "url": "",
+                        "sha1": sha1(universal),
+                        "size": universal.len()
+                    }
+                }
+            },
+            {
+                "name": "net.minecraftforge:forge:1.20.4-49.0.3:client",
+                "downloads": {
+                    "artifact": {
+                        "path": "net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-client.jar",
+                        "url": "",
+                        "sha1": sha1(patched),
+                        "size": patched.len()
+                    }
+                }
+            }
+        ]
+    })
+    .to_string();
+
+    let installer = zip(&[
+        ("install_profile.json", profile.as_bytes()),
+        ("version.json", version.as_bytes()),
+        ("data/client.lzma", b"binpatch"),
+        (
+            "maven/net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-universal.jar",
+            universal,
+        ),
+    ]);
+
+    let installer_path =
+        "/forge-maven/net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-installer.jar";
+    let mut mocks = serve_vanilla(&mut server, "1.20.4").await;
+    mocks.extend([
+        serve(&mut server, installer_path, &installer).await,
+        serve(
+            &mut server,
+            &format!("{}.sha1", installer_path),
+            sha1(&installer).as_bytes(),
+        )
+        .await,
+        serve(
+            &mut server,
+            "/forge-maven/net/minecraftforge/binarypatcher/1.1.1/binarypatcher-1.1.1.jar",
+            &binarypatcher,
+        )
+        .await,
+    ]);
+
+    let downloader = mirror_downloader(&server);
+    let forge = ForgeVersionBuilder::new("1.20.4", "49.0.3")
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(forge.id(), "1.20.4-forge-49.0.3");
+    assert_eq!(forge.manifest().jar(), "1.20.4");
+
+    // Everything is installed into the layout of the instance
+    let instance = TestInstance::new(forge);
+    let layout = instance.layout();
+    let patched_path = layout
+        .libraries_dir()
+        .join("net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-client.jar");
+
+    // A processor whose output does not match its hash is an error
+    #[cfg(unix)]
+    {
+        let result = ForgeVersion::install(&instance, "true").await;
+        assert!(
+            matches!(result, Err(ForgeInstallError::InvalidOutput(_))),
+            "{:?}",
+            result
+        );
+    }
+
+    // The processors with up to date outputs are not run
+    std::fs::create_dir_all(patched_path.parent().unwrap()).unwrap();
+    std::fs::write(&patched_path, patched).unwrap();
+    // An extracted library left incomplete is replaced
+    let universal_path = layout
+        .libraries_dir()
+        .join("net/minecraftforge/forge/1.20.4-49.0.3/forge-1.20.4-49.0.3-universal.jar");
+    std::fs::write(&universal_path, b"trunc").unwrap();
+    ForgeVersion::install(&instance, "/nonexistent/java")
+        .await
+        .unwrap();
+
+    // Only the instances of a Forge version are installed
+    let vanilla = VanillaVersionBuilder::new("1.20.4")
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+    let result = ForgeVersion::install(&TestInstance::new(vanilla), "/nonexistent/java").await;
+    assert!(matches!(result, Err(ForgeInstallError::NotForge)));
+
+    // The libraries generated by the installer are not downloaded by the updater
+    instance.update(downloader, mocks).await;
+
+    assert_eq!(std::fs::read(&universal_path).unwrap(), universal);
+}
@@ -0,0 +1,166 @@
+//! Install a legacy Forge version, its libraries being fetched from the Forge maven or from
+//! Maven Central when the maven copy matches none of their checksums.
+
+use bauxite::{
+    minecraft::version::MinecraftVersion,
+    modloaders::forge::{
+        legacy::{LegacyForgeVersion, LegacyForgeVersionBuilder},
+        ForgeInstallError,
+    },
+};
+use mockito::Server;
+
+mod common;
+use common::{mirror_downloader, serve, serve_vanilla, sha1, zip, TestInstance};
+
+#[tokio::test]
+async fn install_legacy_forge() {
+    let mut server = Server::new_async().await;
+
+    let universal = b"universal";
+    let scala = b"scala";
+
+    let profile = serde_json::json!({
+        "install": {
+            "profileName": "Forge",
+            "target": "1.7.10-Forge10.13.4.1614-1.7.10",
+            "path": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10",
+            "version": "Forge 10.13.4.1614",
+            "filePath": "forge-1.7.10-10.13.4.1614-1.7.10-universal.jar",
+            "minecraft": "1.7.10"
+        },
+        "versionInfo": {
+            "id": "1.7.10-Forge10.13.4.1614-1.7.10",
+            "time": "2015-06-25T17:58:54+0000",
+            "releaseTime": "1960-01-01T00:00:00-0700",
+            "type": "release",
+            "minecraftArguments": "--username ${auth_player_name} --tweakClass cpw.mods.fml.common.launcher.FMLTweaker",
+            "mainClass": "net.minecraft.launchwrapper.Launch",
+            "minimumLauncherVersion": 13,
+            "libraries": [
+                {
+                    "name": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10",
+                    "url": "http://files.minecraftforge.net/maven/"
+                },
+                { "name": "net.minecraft:launchwrapper:1.12" },
+                {
+                    "name": "org.scala-lang:scala-library:2.11.1",
+                    "url": "http://files.minecraftforge.net/maven/",
+                    "checksums": [sha1(scala)],
+                    "serverreq": true,
+                    "clientreq": true
+                },
+                { "name": "lzma:lzma:0.0.1", "serverreq": true },
+                { "name": "com.mojang:realms:1.3.5", "clientreq": false }
+            ]
+        }
+    })
+    .to_string();
+
+    let installer = zip(&[
+        ("install_profile.json", profile.as_bytes()),
+        ("forge-1.7.10-10.13.4.1614-1.7.10-universal.jar", universal),
+    ]);
+
+    let installer_path = "/forge-maven/net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10-installer.jar";
+    let scala_path = "org/scala-lang/scala-library/2.11.1/scala-library-2.11.1.jar";
+    serve_vanilla(&mut server, "1.7.10").await;
+    let mocks = [
+        serve(&mut server, installer_path, &installer).await,
+        serve(
+            &mut server,
+            &format!("{}.sha1", installer_path),
+            sha1(&installer).as_bytes(),
+        )
+        .await,
+        // The pack200 era copy, matching none of the checksums
+        serve(
+            &mut server,
+            &format!("/forge-maven/{}", scala_path),
+            b"repacked",
+        )
+        .await,
+        serve(&mut server, &format!("/central/{}", scala_path), scala).await,
+    ];
+
+    let forge = LegacyForgeVersionBuilder::new("1.7.10", "10.13.4.1614-1.7.10")
+        .with_downloader(mirror_downloader(&server))
+        .build()
+        .await
+        .unwrap();
+
+    let manifest = forge.manifest();
+    assert_eq!(forge.id(), "1.7.10-Forge10.13.4.1614-1.7.10");
+    assert_eq!(manifest.main_class, "net.minecraft.launchwrapper.Launch");
+    assert!(manifest
+        .minecraft_arguments
+        .as_deref()
+        .unwrap()
+        .contains("--tweakClass cpw.mods.fml.common.launcher.FMLTweaker"));
+    assert_eq!(manifest.jar(), "1.7.10");
+
+    let libraries = manifest
+        .libraries
+        .iter()
+        .map(|library| library.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        libraries,
+        [
+            "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10",
+            "net.minecraft:launchwrapper:1.12",
+            "org.scala-lang:scala-library:2.11.1",
+            "lzma:lzma:0.0.1"
+        ]
+    );
+
+    // Everything is installed into the layout of the instance
+    let instance = TestInstance::new(forge);
+    let libraries_dir = instance.layout().libraries_dir();
+
+    // An existing library matching none of its checksums is downloaded again
+    std::fs::create_dir_all(libraries_dir.join(scala_path).parent().unwrap()).unwrap();
+    std::fs::write(libraries_dir.join(scala_path), b"corrupted").unwrap();
+
+    LegacyForgeVersion::install(&instance).await.unwrap();
+
+    let [installer_mock, installer_sha1_mock, forge_scala_mock, central_scala_mock] = mocks;
+    for mock in [
+        &installer_mock,
+        &installer_sha1_mock,
+        &forge_scala_mock,
+        &central_scala_mock,
+    ] {
+        mock.assert_async().await;
+    }
+
+    assert_eq!(
+        std::fs::read(libraries_dir.join(
+            "net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10.jar"
+        ))
+        .unwrap(),
+        universal
+    );
+    assert_eq!(
+        std::fs::read(libraries_dir.join(scala_path)).unwrap(),
+        scala
+    );
+
+    // A library matching none of its checksums on Maven Central either is rejected
+    std::fs::remove_file(libraries_dir.join(scala_path)).unwrap();
+    central_scala_mock.remove_async().await;
+    serve(
+        &mut server,
+        &format!("/central/{}", scala_path),
+        b"tampered",
+    )
+    .await;
+
+    let result = LegacyForgeVersion::install(&instance).await;
+    assert!(
+        matches!(&result, Err(ForgeInstallError::InvalidChecksum(path)) if path == scala_path),
+        "{:?}",
+        result
+    );
+    assert!(!libraries_dir.join(scala_path).exists());
+}
@@ -0,0 +1,165 @@
+//! List the mods of a folder holding jars built for every supported loader.
+
+use bauxite::mods::metadata::{list_mods, DependencyKind, ModLoader, ModMetadataError};
+
+mod common;
+use common::zip;
+
+#[test]
+fn list_installed_mods() {
+    let dir = std::env::temp_dir().join("bauxite-mod-metadata-test");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let fabric = serde_json::json!({
+        "schemaVersion": 1,
+        "id": "sodium",
+        "version": "0.5.8",
+        "name": "Sodium",
+        "icon": "assets/sodium/icon.png",
+        "depends": { "minecraft": "1.20.4" }
+    })
+    .to_string();
+    let quilt = serde_json::json!({
+        "schema_version": 1,
+        "quilt_loader": {
+            "group": "org.quiltmc",
+            "id": "qsl",
+            "version": "8.0.0",
+            "metadata": { "name": "Quilt Standard Libraries" },
+            "depends": [
+                "quilt_loader",
+                { "id": "org.quiltmc:quilt_base", "versions": ">=8.0.0" },
+                { "id": "modmenu", "optional": true }
+            ]
+        }
+    })
+    .to_string();
+    let neoforge = r#"
+        modLoader = "javafml"
+        loaderVersion = "[1,)"
+        license = "MIT"
+
+        [[mods]]
+        modId = "jei"
+        version = "${file.jarVersion}"
+        displayName = "Just Enough Items"
+        logoFile = "logo.png"
+
+        [[dependencies.jei]]
+        modId = "neoforge"
+        type = "required"
+        versionRange = "[20.4,)"
+        side = "BOTH"
+    "#;
+    let mcmod = serde_json::json!({
+        "modListVersion": 2,
+        "modList": [{
+            "modid": "journeymap",
+            "name": "JourneyMap",
+            "version": "5.1.4",
+            "logoFile": "/assets/journeymap/logo.png",
+            "requiredMods": ["Forge@[10.13.4.1558,)"],
+            "dependencies": ["Forge", "waila"]
+        }]
+    })
+    .to_string();
+
+    let jars: [(&str, Vec<u8>); 6] = [
+        (
+            "sodium.jar",
+            zip(&[
+                ("fabric.mod.json", fabric.as_bytes()),
+                ("assets/sodium/icon.png", b"png"),
+            ]),
+        ),
+        ("qsl.jar", zip(&[("quilt.mod.json", quilt.as_bytes())])),
+        (
+            "jei.jar",
+            zip(&[
+                // The values longer than the line limit continue on the next lines
+                (
+                    "META-INF/MANIFEST.MF",
+                    b"Manifest-Version: 1.0\r\nImplementation-Version: 17.3\r\n .0.49\r\nImplementation-Title: jei\r\n",
+                ),
+                ("META-INF/neoforge.mods.toml", neoforge.as_bytes()),
+            ]),
+        ),
+        ("journeymap.jar", zip(&[("mcmod.info", mcmod.as_bytes())])),
+        ("library.jar", zip(&[("com/example/Library.class", b"")])),
+        ("broken.jar", zip(&[("fabric.mod.json", b"{")])),
+    ];
+    for (name, jar) in jars {
+        std::fs::write(dir.join(name), jar).unwrap();
+    }
+    std::fs::write(dir.join("options.txt"), b"").unwrap();
+
+    let list = list_mods(&dir).unwrap();
+
+    assert_eq!(list.errors.len(), 1);
+    assert_eq!(list.errors[0].0, dir.join("broken.jar"));
+    assert!(matches!(
+        list.errors[0].1,
+        ModMetadataError::ParseJson(_, _)
+    ));
+
+    let mods = list
+        .mods
+        .iter()
+        .map(|info| (info.id.as_str(), info.version.as_str(), info.loader))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        mods,
+        [
+            ("jei", "17.3.0.49", ModLoader::NeoForge),
+            ("journeymap", "5.1.4", ModLoader::Forge),
+            ("qsl", "8.0.0", ModLoader::Quilt),
+            ("sodium", "0.5.8", ModLoader::Fabric),
+        ]
+    );
+
+    let jei = &list.mods[0];
+    assert_eq!(jei.name, "Just Enough Items");
+    assert_eq!(jei.icon.as_deref(), Some("logo.png"));
+    assert_eq!(jei.dependencies[0].id, "neoforge");
+    assert_eq!(jei.dependencies[0].version_range, "[20.4,)");
+
+    let journeymap = &list.mods[1];
+    assert_eq!(
+        journeymap.icon.as_deref(),
+        Some("assets/journeymap/logo.png")
+    );
+    let dependencies = journeymap
+        .dependencies
+        .iter()
+        .map(|d| (d.id.as_str(), d.version_range.as_str(), d.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dependencies,
+        [
+            ("Forge", "[10.13.4.1558,)", DependencyKind::Required),
+            ("waila", "*", DependencyKind::Optional)
+        ]
+    );
+
+    let qsl = &list.mods[2];
+    let dependencies = qsl
+        .dependencies
+        .iter()
+        .map(|d| (d.id.as_str(), d.version_range.as_str(), d.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dependencies,
+        [
+            ("quilt_loader", "*", DependencyKind::Required),
+            ("quilt_base", ">=8.0.0", DependencyKind::Required),
+            ("modmenu", "*", DependencyKind::Optional)
+        ]
+    );
+
+    let sodium = &list.mods[3];
+    assert_eq!(sodium.name, "Sodium");
+    assert_eq!(sodium.read_icon().unwrap().as_deref(), Some(&b"png"[..]));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,346 @@
+//! Search and download mods from a local stand-in for the Modrinth API.
+
+use std::time::{Duration, Instant};
+
+use bauxite::{
+    minecraft::vanilla::VanillaVersionBuilder,
+    mods::{
+        metadata::ModLoader,
+        modrinth::{ModrinthClient, ModrinthError, SearchQuery},
+        resolver::{Conflict, ResolveError, Resolver},
+    },
+    DownloadError, InstanceBuilder,
+};
+use mockito::{Matcher, Server};
+
+mod common;
+use common::{mirror_downloader, serve, serve_vanilla, sha1, sha512};
+
+fn version_json(server: &Server, content: &[u8], sha512: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "mOgUt4GM",
+        "project_id": "AANobbMI",
+        "name": "Sodium 0.5.8",
+        "version_number": "mc1.20.4-0.5.8",
+        "version_type": "release",
+        "game_versions": ["1.20.4"],
+        "loaders": ["fabric"],
+        "files": [
+            {
+                "hashes": { "sha1": sha1(b"sources"), "sha512": "00" },
+                "url": format!("{}/cdn/sodium-sources.jar", server.url()),
+                "filename": "sodium-sources.jar",
+                "primary": false,
+                "size": 7
+            },
+            {
+                "hashes": { "sha1": sha1(content), "sha512": sha512 },
+                "url": format!("{}/cdn/sodium.jar", server.url()),
+                "filename": "sodium.jar",
+                "primary": true,
+                "size": content.len()
+            }
+        ],
+        "dependencies": [
+            { "project_id": "P7dR8mSH", "dependency_type": "required" }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn search_and_download() {
+    let mut server = Server::new_async().await;
+    let root = std::env::temp_dir().join("bauxite-modrinth-test");
+    let _ = std::fs::remove_dir_all(&root);
+
+    let jar = b"sodium";
+    let search = serde_json::json!({
+        "hits": [{
+            "project_id": "AANobbMI",
+            "project_type": "mod",
+            "slug": "sodium",
+            "title": "Sodium",
+            "description": "The fastest rendering optimization mod",
+            "author": "jellysquid3",
+            "categories": ["fabric", "optimization"],
+            "versions": ["1.20.4"],
+            "downloads": 1000,
+            "latest_version": "mOgUt4GM"
+        }],
+        "offset": 0,
+        "limit": 10,
+        "total_hits": 1
+    })
+    .to_string();
+    let versions = serde_json::json!([version_json(&server, jar, &sha512(jar))]).to_string();
+
+    serve_vanilla(&mut server, "1.20.4").await;
+    let mocks = vec![
+        server
+            .mock("GET", "/modrinth/search")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("query".into(), "sodium".into()),
+                Matcher::UrlEncoded(
+                    "facets".into(),
+                    r#"[["versions:1.20.4"],["categories:fabric"]]"#.into(),
+                ),
+            ]))
+            .match_header("user-agent", "test-launcher/1.0")
+            .with_body(search)
+            .create_async()
+            .await,
+        server
+            .mock("GET", "/modrinth/project/AANobbMI/version")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("game_versions".into(), r#"["1.20.4"]"#.into()),
+                Matcher::UrlEncoded("loaders".into(), r#"["fabric"]"#.into()),
+            ]))
+            .with_body(versions)
+            .create_async()
+            .await,
+        serve(&mut server, "/cdn/sodium.jar", jar).await,
+    ];
+
+    let downloader = mirror_downloader(&server);
+    let client = ModrinthClient::new()
+        .with_downloader(downloader.clone())
+        .with_user_agent("test-launcher/1.0");
+
+    let results = client
+        .search(
+            &SearchQuery::new("sodium")
+                .with_game_version("1.20.4")
+                .with_loader("fabric"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.hits.len(), 1);
+    assert_eq!(results.hits[0].slug, "sodium");
+
+    let versions = client
+        .project_versions(&results.hits[0].project_id, &["1.20.4"], &["fabric"])
+        .await
+        .unwrap();
+    assert_eq!(versions[0].version_number, "mc1.20.4-0.5.8");
+
+    let vanilla = VanillaVersionBuilder::new("1.20.4")
+        .with_downloader(downloader)
+        .build()
+        .await
+        .unwrap();
+    let instance = InstanceBuilder::new(vanilla)
+        .with_output_dir(root.join("game"))
+        .with_cache_dir(root.join("cache"))
+        .build();
+
+    let path = client
+        .download_version(&versions[0], &instance)
+        .await
+        .unwrap();
+    assert_eq!(path, instance.mods_dir().join("sodium.jar"));
+    assert_eq!(std::fs::read(&path).unwrap(), jar);
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn reject_invalid_sha512() {
+    let mut server = Server::new_async().await;
+    let dir = std::env::temp_dir().join("bauxite-modrinth-sha512-test");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let version = version_json(&server, b"sodium", &sha512(b"other"));
+    server
+        .mock("GET", "/modrinth/version/mOgUt4GM")
+        .with_body(version.to_string())
+        .create_async()
+        .await;
+    serve(&mut server, "/cdn/sodium.jar", b"sodium").await;
+
+    let client = ModrinthClient::new().with_downloader(mirror_downloader(&server));
+    let version = client.version("mOgUt4GM").await.unwrap();
+    let result = client
+        .download_file(version.primary_file().unwrap(), &dir)
+        .await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ModrinthError::DownloadError(DownloadError::InvalidChecksum))
+        ),
+        "{:?}",
+        result
+    );
+    assert!(!dir.join("sodium.jar").exists());
+}
+
+#[tokio::test]
+async fn wait_for_rate_limit() {
+    let mut server = Server::new_async().await;
+    let version = version_json(&server, b"sodium", &sha512(b"sodium")).to_string();
+
+    let mocks = vec![
+        server
+            .mock("GET", "/modrinth/version/mOgUt4GM")
+            .with_status(429)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "1")
+            .expect(1)
+            .create_async()
+            .await,
+        server
+            .mock("GET", "/modrinth/version/mOgUt4GM")
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "1")
+            .with_body(&version)
+            .expect(2)
+            .create_async()
+            .await,
+    ];
+
+    let client = ModrinthClient::new().with_downloader(mirror_downloader(&server));
+    let start = Instant::now();
+
+    // Rejected, retried once the limit is reset
+    client.version("mOgUt4GM").await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    // The limit is reached again, the next request waits for its reset
+    client.version("mOgUt4GM").await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(2));
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+}
+
+#[tokio::test]
+async fn resolve_project_dependencies() {
+    let mut server = Server::new_async().await;
+
+    let sodium = version_json(&server, b"sodium", &sha512(b"sodium"));
+    let mut fabric_api = version_json(&server, b"fabric-api", &sha512(b"fabric-api"));
+    fabric_api["project_id"] = "P7dR8mSH".into();
+    fabric_api["version_number"] = "0.97.0+1.20.4".into();
+    fabric_api["dependencies"] = serde_json::json!([
+        { "project_id": "AANobbMI", "dependency_type": "optional" }
+    ]);
+
+    for (project, versions) in [("sodium", &sodium), ("P7dR8mSH", &fabric_api)] {
+        server
+            .mock(
+                "GET",
+                format!("/modrinth/project/{}/version", project).as_str(),
+            )
+            .match_query(Matcher::Any)
+            .with_body(serde_json::json!([versions]).to_string())
+            .create_async()
+            .await;
+    }
+    server
+        .mock("GET", "/modrinth/project/unknown/version")
+        .match_query(Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let client = ModrinthClient::new().with_downloader(mirror_downloader(&server));
+    let resolver = Resolver::new(client, "1.20.4", ModLoader::Fabric);
+
+    let plan = resolver.resolve(&["sodium"]).await.unwrap();
+    let mods = plan
+        .mods
+        .iter()
+        .map(|candidate| {
+            (
+                candidate.id.as_str(),
+                candidate.data.version_number.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        mods,
+        [
+            ("AANobbMI", "mc1.20.4-0.5.8"),
+            ("P7dR8mSH", "0.97.0+1.20.4")
+        ]
+    );
+
+    let result = resolver.resolve(&["sodium", "unknown"]).await;
+    assert!(
+        matches!(
+            &result,
+            Err(ResolveError::Conflict(Conflict::Missing { id, required_by: None })) if id == "unknown"
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn resolve_pinned_dependencies() {
+    let mut server = Server::new_async().await;
+
+    let mut sodium = version_json(&server, b"sodium", &sha512(b"sodium"));
+    // The dependencies on a version may not have the project
+    sodium["dependencies"] = serde_json::json!([
+        { "version_id": "Y0pDqKjL", "dependency_type": "required" }
+    ]);
+    let mut latest = version_json(&server, b"fabric-api", &sha512(b"fabric-api"));
+    latest["id"] = "Rl3bB4Z9".into();
+    latest["project_id"] = "P7dR8mSH".into();
+    latest["version_number"] = "0.97.1+1.20.4".into();
+    latest["dependencies"] = serde_json::json!([]);
+    let mut pinned = latest.clone();
+    pinned["id"] = "Y0pDqKjL".into();
+    pinned["version_number"] = "0.97.0+1.20.4".into();
+
+    for (project, versions) in [
+        ("sodium", serde_json::json!([sodium])),
+        ("P7dR8mSH", serde_json::json!([latest, pinned])),
+    ] {
+        server
+            .mock(
+                "GET",
+                format!("/modrinth/project/{}/version", project).as_str(),
+            )
+            .match_query(Matcher::Any)
+            .with_body(versions.to_string())
+            .create_async()
+            .await;
+    }
+    let pinned_mock = serve(
+        &mut server,
+        "/modrinth/version/Y0pDqKjL",
+        pinned.to_string().as_bytes(),
+    )
+    .await;
+
+    let client = ModrinthClient::new().with_downloader(mirror_downloader(&server));
+    let resolver = Resolver::new(client, "1.20.4", ModLoader::Fabric);
+
+    let plan = resolver.resolve(&["sodium"]).await.unwrap();
+    let mods = plan
+        .mods
+        .iter()
+        .map(|candidate| {
+            (
+                candidate.id.as_str(),
+                candidate.data.version_number.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        mods,
+        [
+            ("AANobbMI", "mc1.20.4-0.5.8"),
+            ("P7dR8mSH", "0.97.0+1.20.4")
+        ]
+    );
+
+    pinned_mock.assert_async().await;
+}
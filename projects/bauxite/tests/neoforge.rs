@@ -0,0 +1,193 @@
+//! Install a NeoForge version discovered from a local stand-in for the NeoForge maven.
+
+use bauxite::{
+    minecraft::version::MinecraftVersion,
+    modloaders::{
+        forge::ForgeVersion,
+        neoforge::{fetch_neoforge_versions, NeoForgeVersionBuilder},
+    },
+};
+use mockito::Server;
+
+mod common;
+use common::{mirror_downloader, serve, serve_vanilla, sha1, zip, TestInstance};
+
+#[tokio::test]
+async fn install_neoforge() {
+    let mut server = Server::new_async().await;
+
+    let metadata = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.neoforged</groupId>
+  <artifactId>neoforge</artifactId>
+  <versioning>
+    <latest>20.5.0-beta</latest>
+    <release>20.5.0-beta</release>
+    <versions>
+      <version>20.2.86</version>
+      <version>20.4.236</version>
+      <version>20.4.237</version>
+      <version>20.4.238-beta</version>
+      <version>20.5.0-beta</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+
+    let universal = b"universal";
+    let profile = serde_json::json!({
+        "spec": 1,
+        "profile": "NeoForge",
+        "version": "neoforge-20.4.237",
+        "minecraft": "1.20.4",
+        "json": "/version.json",
+        "data": {},
+        "processors": [],
+        "libraries": []
+    })
+    .to_string();
+    let version = serde_json::json!({
+        "id": "neoforge-20.4.237",
+        "inheritsFrom": "1.20.4",
+        "type": "release",
+        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+        "arguments": { "game": ["--launchTarget", "forgeclient"] },
+        "libraries": [
+            {
+                "name": "net.neoforged:neoforge:20.4.237:universal",
+                "downloads": {
+                    "artifact": {
+                        "path": "net/neoforged/neoforge/20.4.237/neoforge-20.4.237-universal.jar",
+                        "url": "https://maven.neoforged.net/releases/net/neoforged/neoforge/20.4.237/neoforge-20.4.237-universal.jar",
+                        "sha1": sha1(universal),
+                        "size": universal.len()
+                    }
+                }
+            }
+        ]
+    })
+    .to_string();
+    let installer = zip(&[
+        ("install_profile.json", profile.as_bytes()),
+        ("version.json", version.as_bytes()),
+    ]);
+
+    let mut mocks = serve_vanilla(&mut server, "1.20.4").await;
+    mocks.extend([
+        serve(
+            &mut server,
+            "/neoforge-maven/net/neoforged/neoforge/maven-metadata.xml",
+            metadata.as_bytes(),
+        )
+        .await,
+        serve(
+            &mut server,
+            "/neoforge-maven/net/neoforged/neoforge/20.4.237/neoforge-20.4.237-installer.jar",
+            &installer,
+        )
+        .await,
+        serve(
+            &mut server,
+            "/neoforge-maven/net/neoforged/neoforge/20.4.237/neoforge-20.4.237-universal.jar",
+            universal,
+        )
+        .await,
+    ]);
+
+    let downloader = mirror_downloader(&server);
+
+    let versions = fetch_neoforge_versions(&downloader).await.unwrap();
+    assert_eq!(versions.len(), 5);
+
+    // The latest stable version of the game version is used by default
+    let neoforge = NeoForgeVersionBuilder::new("1.20.4")
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(neoforge.forge_version(), "20.4.237");
+    assert_eq!(neoforge.id(), "neoforge-20.4.237");
+    assert_eq!(neoforge.manifest().jar(), "1.20.4");
+
+    let instance = TestInstance::new(neoforge);
+    ForgeVersion::install(&instance, "/nonexistent/java")
+        .await
+        .unwrap();
+    instance.update(downloader, mocks).await;
+
+    assert!(instance
+        .layout()
+        .libraries_dir()
+        .join("net/neoforged/neoforge/20.4.237/neoforge-20.4.237-universal.jar")
+        .exists());
+}
+
+#[tokio::test]
+async fn build_legacy_neoforge() {
+    let mut server = Server::new_async().await;
+
+    // The 1.20.1 builds are published next to the Forge ones
+    let metadata = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.neoforged</groupId>
+  <artifactId>forge</artifactId>
+  <versioning>
+    <versions>
+      <version>1.20.1-47.1.7</version>
+      <version>1.20.1-47.1.106</version>
+      <version>1.20.1-47.1.54</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+
+    let profile = serde_json::json!({
+        "spec": 1,
+        "profile": "forge",
+        "version": "1.20.1-forge-47.1.106",
+        "minecraft": "1.20.1",
+        "json": "/version.json",
+        "data": {},
+        "processors": [],
+        "libraries": []
+    })
+    .to_string();
+    let version = serde_json::json!({
+        "id": "1.20.1-forge-47.1.106",
+        "inheritsFrom": "1.20.1",
+        "type": "release",
+        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+        "libraries": []
+    })
+    .to_string();
+    let installer = zip(&[
+        ("install_profile.json", profile.as_bytes()),
+        ("version.json", version.as_bytes()),
+    ]);
+
+    serve_vanilla(&mut server, "1.20.1").await;
+    let mocks = [
+        serve(
+            &mut server,
+            "/neoforge-maven/net/neoforged/forge/maven-metadata.xml",
+            metadata.as_bytes(),
+        )
+        .await,
+        serve(
+            &mut server,
+            "/neoforge-maven/net/neoforged/forge/1.20.1-47.1.106/forge-1.20.1-47.1.106-installer.jar",
+            &installer,
+        )
+        .await,
+    ];
+
+    let neoforge = NeoForgeVersionBuilder::new("1.20.1")
+        .with_downloader(mirror_downloader(&server))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(neoforge.forge_version(), "47.1.106");
+    assert_eq!(neoforge.id(), "1.20.1-forge-47.1.106");
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,113 @@
+//! Install a Quilt version from a local stand-in for the Quilt meta API.
+
+use bauxite::{minecraft::version::MinecraftVersion, modloaders::quilt::QuiltVersionBuilder};
+use mockito::Server;
+
+mod common;
+use common::{mirror_downloader, serve, serve_meta, serve_vanilla, sha1, TestInstance};
+
+#[tokio::test]
+async fn install_quilt() {
+    let mut server = Server::new_async().await;
+
+    let loader = b"quilt-loader";
+    let hashed = b"hashed";
+    let intermediary = b"intermediary";
+
+    let loaders = serde_json::json!([
+        {
+            "loader": { "separator": ".", "build": 2, "maven": "org.quiltmc:quilt-loader:0.24.0-beta.1", "version": "0.24.0-beta.1" },
+            "hashed": { "maven": "org.quiltmc:hashed:1.20.4", "version": "1.20.4" },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4" }
+        },
+        {
+            "loader": { "separator": ".", "build": 1, "maven": "org.quiltmc:quilt-loader:0.23.1", "version": "0.23.1" },
+            "hashed": { "maven": "org.quiltmc:hashed:1.20.4", "version": "1.20.4" },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.4", "version": "1.20.4" }
+        }
+    ])
+    .to_string();
+
+    let library = |name: &str, content: &[u8]| {
+        serde_json::json!({
+            "name": name,
+            "url": format!("{}/quilt-maven/", server.url()),
+            "sha1": sha1(content),
+            "size": content.len()
+        })
+    };
+    let profile = serde_json::json!({
+        "id": "quilt-loader-0.23.1-1.20.4",
+        "inheritsFrom": "1.20.4",
+        "type": "release",
+        "mainClass": "org.quiltmc.loader.impl.launch.knot.KnotClient",
+        "arguments": { "game": [] },
+        "libraries": [
+            library("org.quiltmc:quilt-loader:0.23.1", loader),
+            library("org.quiltmc:hashed:1.20.4", hashed),
+            library("net.fabricmc:intermediary:1.20.4", intermediary)
+        ],
+        "releaseTime": "2024-01-05T12:00:00.000Z",
+        "time": "2024-01-05T12:00:00.000Z"
+    })
+    .to_string();
+
+    let mut mocks = serve_vanilla(&mut server, "1.20.4").await;
+    mocks.extend(
+        serve_meta(
+            &mut server,
+            "quilt-meta",
+            "1.20.4",
+            "0.23.1",
+            &loaders,
+            &profile,
+        )
+        .await,
+    );
+    mocks.extend([
+        serve(
+            &mut server,
+            "/quilt-maven/org/quiltmc/quilt-loader/0.23.1/quilt-loader-0.23.1.jar",
+            loader,
+        )
+        .await,
+        serve(
+            &mut server,
+            "/quilt-maven/org/quiltmc/hashed/1.20.4/hashed-1.20.4.jar",
+            hashed,
+        )
+        .await,
+        serve(
+            &mut server,
+            "/quilt-maven/net/fabricmc/intermediary/1.20.4/intermediary-1.20.4.jar",
+            intermediary,
+        )
+        .await,
+    ]);
+
+    let downloader = mirror_downloader(&server);
+
+    // The pre-releases are skipped by default
+    let quilt = QuiltVersionBuilder::new("1.20.4")
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(quilt.loader_version(), "0.23.1");
+    assert_eq!(quilt.loader().hashed.maven, "org.quiltmc:hashed:1.20.4");
+    assert_eq!(
+        quilt.loader().intermediary.maven,
+        "net.fabricmc:intermediary:1.20.4"
+    );
+    assert_eq!(quilt.id(), "quilt-loader-0.23.1-1.20.4");
+    assert_eq!(quilt.manifest().assets, "12");
+
+    let instance = TestInstance::new(quilt);
+    instance.update(downloader, mocks).await;
+
+    let layout = instance.layout();
+    assert!(layout
+        .libraries_dir()
+        .join("org/quiltmc/hashed/1.20.4/hashed-1.20.4.jar")
+        .exists());
+}
@@ -0,0 +1,165 @@
+//! Rule evaluation against the version JSONs in `tests/fixtures`, trimmed to the
+//! libraries and arguments carrying rules.
+
+use bauxite::minecraft::{
+    arguments::{game_arguments, jvm_arguments, ArgumentContext},
+    classpath::Classpath,
+    jsons::version_manifest::McVersionManifest,
+    natives::native_libraries,
+    rules::{OsName, RuleContext},
+};
+
+fn fixture(id: &str) -> McVersionManifest {
+    let path = format!("{}/tests/fixtures/{}.json", env!("CARGO_MANIFEST_DIR"), id);
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn classpath(manifest: &McVersionManifest, rules: &RuleContext) -> Vec<String> {
+    Classpath::from_manifest(manifest, rules, "", "client.jar")
+        .entries()
+        .iter()
+        .map(|entry| entry.to_string_lossy().to_string())
+        .collect()
+}
+
+fn natives(manifest: &McVersionManifest, rules: &RuleContext) -> Vec<String> {
+    native_libraries(manifest, rules)
+        .iter()
+        .map(|native| native.artifact.path.clone())
+        .collect()
+}
+
+#[test]
+fn legacy_libraries_linux() {
+    let manifest = fixture("1.7.10");
+    let rules = RuleContext::new(OsName::Linux, "6.1.0", "x86_64");
+
+    let classpath = classpath(&manifest, &rules);
+    assert!(classpath.contains(&"org/lwjgl/lwjgl/lwjgl/2.9.1/lwjgl-2.9.1.jar".to_string()));
+    assert!(!classpath.contains(&"org/lwjgl/lwjgl/lwjgl/2.9.0/lwjgl-2.9.0.jar".to_string()));
+
+    assert_eq!(
+        natives(&manifest, &rules),
+        [
+            "org/lwjgl/lwjgl/lwjgl-platform/2.9.1/lwjgl-platform-2.9.1-natives-linux.jar",
+            "net/java/jinput/jinput-platform/2.0.5/jinput-platform-2.0.5-natives-linux.jar",
+        ]
+    );
+}
+
+#[test]
+fn legacy_libraries_osx() {
+    let manifest = fixture("1.7.10");
+    let rules = RuleContext::new(OsName::MacOs, "13.4", "x86_64");
+
+    let classpath = classpath(&manifest, &rules);
+    assert!(classpath.contains(&"org/lwjgl/lwjgl/lwjgl/2.9.0/lwjgl-2.9.0.jar".to_string()));
+    assert!(!classpath.contains(&"org/lwjgl/lwjgl/lwjgl/2.9.1/lwjgl-2.9.1.jar".to_string()));
+
+    assert_eq!(
+        natives(&manifest, &rules),
+        [
+            "tv/twitch/twitch-platform/5.16/twitch-platform-5.16-natives-osx.jar",
+            "org/lwjgl/lwjgl/lwjgl-platform/2.9.0/lwjgl-platform-2.9.0-natives-osx.jar",
+            "net/java/jinput/jinput-platform/2.0.5/jinput-platform-2.0.5-natives-osx.jar",
+        ]
+    );
+}
+
+#[test]
+fn legacy_natives_windows_arch() {
+    let manifest = fixture("1.7.10");
+    let rules = RuleContext::new(OsName::Windows, "10.0.19045", "x86_64");
+
+    let natives = natives(&manifest, &rules);
+    assert!(natives.contains(
+        &"tv/twitch/twitch-external-platform/4.5/twitch-external-platform-4.5-natives-windows-64.jar"
+            .to_string()
+    ));
+    assert!(natives.contains(
+        &"org/lwjgl/lwjgl/lwjgl-platform/2.9.1/lwjgl-platform-2.9.1-natives-windows.jar"
+            .to_string()
+    ));
+}
+
+#[test]
+fn modern_natives_linux() {
+    let manifest = fixture("1.19");
+    let rules = RuleContext::new(OsName::Linux, "6.1.0", "x86_64");
+
+    let natives = natives(&manifest, &rules);
+    assert_eq!(natives.len(), 4);
+    assert!(natives
+        .iter()
+        .all(|path| path.ends_with("-natives-linux.jar")));
+
+    let classpath = classpath(&manifest, &rules);
+    assert!(!classpath
+        .iter()
+        .any(|path| path.contains("java-objc-bridge")));
+    assert!(!classpath
+        .iter()
+        .any(|path| path.contains("natives-windows")));
+}
+
+#[test]
+fn modern_natives_windows_x86_64() {
+    let manifest = fixture("1.19");
+    let rules = RuleContext::new(OsName::Windows, "10.0.19045", "x86_64");
+
+    let natives = natives(&manifest, &rules);
+    assert_eq!(natives.len(), 4);
+    assert!(natives
+        .iter()
+        .all(|path| path.ends_with("-natives-windows.jar")));
+}
+
+#[test]
+fn modern_natives_macos_x86_64() {
+    let manifest = fixture("1.19");
+    let rules = RuleContext::new(OsName::MacOs, "13.4", "x86_64");
+
+    let natives = natives(&manifest, &rules);
+    assert_eq!(natives.len(), 3);
+    assert!(natives
+        .iter()
+        .all(|path| path.ends_with("-natives-macos.jar")));
+}
+
+#[test]
+fn jvm_arguments_os_version_and_arch() {
+    let manifest = fixture("1.19");
+    let context = ArgumentContext::default();
+
+    let windows_10 = RuleContext::new(OsName::Windows, "10.0.19045", "x86_64");
+    let args = jvm_arguments(&manifest, &context, &windows_10);
+    assert!(args.contains(&"-Dos.name=Windows 10".to_string()));
+    assert!(!args.contains(&"-Xss1M".to_string()));
+    assert!(!args.contains(&"-XstartOnFirstThread".to_string()));
+
+    let windows_7 = RuleContext::new(OsName::Windows, "6.1.7601", "x86");
+    let args = jvm_arguments(&manifest, &context, &windows_7);
+    assert!(!args.contains(&"-Dos.name=Windows 10".to_string()));
+    assert!(args.contains(&"-Xss1M".to_string()));
+
+    let osx = RuleContext::new(OsName::MacOs, "13.4", "arm64");
+    let args = jvm_arguments(&manifest, &context, &osx);
+    assert_eq!(args[0], "-XstartOnFirstThread");
+}
+
+#[test]
+fn game_arguments_features() {
+    let manifest = fixture("1.19");
+    let context = ArgumentContext::default();
+    let rules = RuleContext::new(OsName::Linux, "6.1.0", "x86_64");
+
+    let args = game_arguments(&manifest, &context, &rules);
+    assert!(!args.contains(&"--demo".to_string()));
+
+    let args = game_arguments(
+        &manifest,
+        &context,
+        &rules.with_feature("is_demo_user", true),
+    );
+    assert!(args.contains(&"--demo".to_string()));
+}
@@ -0,0 +1,224 @@
+//! The whole update pipeline against a local server, the official URLs of the version JSON
+//! being rewritten to it as a mirror.
+
+use bauxite::{
+    minecraft::{
+        rules::{OsName, RuleContext},
+        vanilla::{VanillaVersion, VanillaVersionBuilder, VanillaVersionError},
+    },
+    DownloaderBuilder, Endpoints, InstanceBuilder, Updater,
+};
+use mockito::Server;
+
+mod common;
+use common::{serve, sha1};
+
+#[tokio::test]
+async fn update_from_mirror() {
+    let mut server = Server::new_async().await;
+    let root = std::env::temp_dir().join("bauxite-updater-test");
+    let _ = std::fs::remove_dir_all(&root);
+
+    let client = b"client";
+    let library = b"library";
+    let asset = b"click";
+
+    let assets_index = serde_json::json!({
+        "virtual": true,
+        "objects": {
+            "sounds/random/click.ogg": { "hash": sha1(asset), "size": asset.len() }
+        }
+    })
+    .to_string();
+
+    let version = serde_json::json!({
+        "assetIndex": {
+            "id": "legacy",
+            "sha1": sha1(assets_index.as_bytes()),
+            "size": assets_index.len(),
+            "totalSize": asset.len(),
+            "url": "https://piston-meta.mojang.com/v1/packages/legacy.json"
+        },
+        "assets": "legacy",
+        "downloads": {
+            "client": {
+                "sha1": sha1(client),
+                "size": client.len(),
+                "url": "https://piston-data.mojang.com/v1/objects/client.jar"
+            }
+        },
+        "id": "1.6.4",
+        "libraries": [
+            {
+                "name": "com.example:library:1.0",
+                "downloads": {
+                    "artifact": {
+                        "path": "com/example/library/1.0/library-1.0.jar",
+                        "sha1": sha1(library),
+                        "size": library.len(),
+                        "url": "https://libraries.minecraft.net/com/example/library/1.0/library-1.0.jar"
+                    }
+                }
+            }
+        ],
+        "mainClass": "net.minecraft.client.main.Main",
+        "minecraftArguments": "--username ${auth_player_name}",
+        "minimumLauncherVersion": 13,
+        "releaseTime": "2013-09-19T15:52:37+00:00",
+        "time": "2013-09-19T15:52:37+00:00",
+        "type": "release"
+    })
+    .to_string();
+
+    let versions = serde_json::json!({
+        "latest": { "release": "1.6.4", "snapshot": "1.6.4" },
+        "versions": [{
+            "id": "1.6.4",
+            "type": "release",
+            "url": "https://piston-meta.mojang.com/v1/packages/1.6.4.json",
+            "sha1": sha1(version.as_bytes()),
+            "complianceLevel": 0,
+            "time": "2013-09-19T15:52:37+00:00",
+            "releaseTime": "2013-09-19T15:52:37+00:00"
+        }]
+    })
+    .to_string();
+
+    let mocks = vec![
+        serve(
+            &mut server,
+            "/mc/game/version_manifest_v2.json",
+            versions.as_bytes(),
+        )
+        .await,
+        serve(&mut server, "/v1/packages/1.6.4.json", version.as_bytes()).await,
+        serve(
+            &mut server,
+            "/v1/packages/legacy.json",
+            assets_index.as_bytes(),
+        )
+        .await,
+        serve(&mut server, "/v1/objects/client.jar", client).await,
+        serve(
+            &mut server,
+            "/maven/com/example/library/1.0/library-1.0.jar",
+            library,
+        )
+        .await,
+        serve(
+            &mut server,
+            &format!("/assets/{}/{}", &sha1(asset)[..2], sha1(asset)),
+            asset,
+        )
+        .await,
+    ];
+
+    let downloader = DownloaderBuilder::new()
+        .with_endpoints(Endpoints::new().with_mirror(&server.url()))
+        .build();
+
+    let vanilla = VanillaVersionBuilder::latest()
+        .with_downloader(downloader.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let instance = InstanceBuilder::new(vanilla)
+        .with_output_dir(root.join("game"))
+        .with_cache_dir(root.join("cache"))
+        .build();
+
+    Updater::new(&instance)
+        .with_downloader(downloader)
+        .with_rule_context(RuleContext::new(OsName::Linux, "6.1.0", "x86_64"))
+        .update()
+        .await
+        .unwrap();
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+
+    let layout = instance.layout();
+    assert_eq!(
+        std::fs::read_to_string(layout.version_json("1.6.4")).unwrap(),
+        version
+    );
+    assert_eq!(std::fs::read(layout.client_jar("1.6.4")).unwrap(), client);
+    assert_eq!(
+        std::fs::read(
+            layout
+                .libraries_dir()
+                .join("com/example/library/1.0/library-1.0.jar")
+        )
+        .unwrap(),
+        library
+    );
+    assert_eq!(
+        std::fs::read(
+            layout
+                .virtual_assets_dir("legacy")
+                .join("sounds/random/click.ogg")
+        )
+        .unwrap(),
+        asset
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// Build the version 1.6.4, served with the given JSON and the SHA1 of `listed`.
+async fn build_with_json(
+    listed: &[u8],
+    json: &[u8],
+) -> Result<VanillaVersion, VanillaVersionError> {
+    let mut server = Server::new_async().await;
+
+    let versions = serde_json::json!({
+        "latest": { "release": "1.6.4", "snapshot": "1.6.4" },
+        "versions": [{
+            "id": "1.6.4",
+            "type": "release",
+            "url": "https://piston-meta.mojang.com/v1/packages/1.6.4.json",
+            "sha1": sha1(listed),
+            "time": "2013-09-19T15:52:37+00:00",
+            "releaseTime": "2013-09-19T15:52:37+00:00"
+        }]
+    })
+    .to_string();
+
+    serve(
+        &mut server,
+        "/mc/game/version_manifest_v2.json",
+        versions.as_bytes(),
+    )
+    .await;
+    serve(&mut server, "/v1/packages/1.6.4.json", json).await;
+
+    let downloader = DownloaderBuilder::new()
+        .with_endpoints(Endpoints::new().with_mirror(&server.url()))
+        .build();
+
+    VanillaVersionBuilder::new("1.6.4")
+        .with_downloader(downloader)
+        .build()
+        .await
+}
+
+#[tokio::test]
+async fn reject_modified_version_json() {
+    let result = build_with_json(b"{}", b"{\"id\": \"1.6.4\"}").await;
+
+    assert!(matches!(result, Err(VanillaVersionError::InvalidChecksum)));
+}
+
+#[tokio::test]
+async fn reject_non_utf8_version_json() {
+    let json = b"{\"id\": \"1.6.4\xff\"}";
+    let result = build_with_json(json, json).await;
+
+    assert!(matches!(
+        result,
+        Err(VanillaVersionError::InvalidEncoding(_))
+    ));
+}
@@ -1,6 +1,9 @@
-use bauxite::minecraft::{
-    vanilla::{fetch_version_json, VanillaVersionBuilder},
-    version::MinecraftVersion,
+use bauxite::{
+    minecraft::{
+        vanilla::{fetch_version_json, VanillaVersionBuilder},
+        version::MinecraftVersion,
+    },
+    Downloader,
 };
 use tokio::task::JoinSet;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -13,7 +16,7 @@ async fn check_all_versions() {
         .init();
 
     // Fetch manifest
-    let manifest = fetch_version_json().await.unwrap();
+    let manifest = fetch_version_json(Downloader::shared()).await.unwrap();
 
     let mut joinset = JoinSet::new();
 
@@ -26,4 +26,10 @@ pub enum JreError {
 
     #[error("The checksum of the file is invalid")]
     InvalidChecksum,
+
+    #[error("There was an error while downloading a file of the JRE")]
+    FileDownloadError(#[from] bauxite_download::DownloadError),
+
+    #[error("There was an error while fetching the manifest")]
+    ManifestFetchError(#[from] bauxite_download::FetchError),
 }
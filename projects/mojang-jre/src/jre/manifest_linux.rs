@@ -27,9 +27,9 @@ impl JreManifestExt for VersionsManifest {
         }
 
         // Get the data array for a specific version type
-        if let Some(data) = data.get(&version_type) {
+        if let Some(data) = data.get(version_type) {
             // Is there a value?
-            if let Some(json_result) = data.get(0) {
+            if let Some(json_result) = data.first() {
                 // Get the first element of the array
                 return Ok(json_result.manifest.url.clone());
             }
@@ -1,14 +1,11 @@
 use std::collections::HashMap;
 
+use bauxite_download::Downloader;
 use serde::Deserialize;
 use time::OffsetDateTime;
 use tracing::debug;
 
-use crate::{
-    constants,
-    errors::{JreError, JreResult},
-    jre::manifest_files::JreManifestFilesList,
-};
+use crate::{errors::JreResult, jre::manifest_files::JreManifestFilesList};
 
 use super::{file::RawFile, manifest_files::JreFile};
 
@@ -51,15 +48,11 @@ pub struct VersionsManifest {
 }
 
 impl VersionsManifest {
-    /// Get the JRE manifest from Mojang servers.
-    pub async fn get() -> JreResult<Self> {
+    /// Get the JRE manifest from the endpoints of the downloader.
+    pub async fn get(downloader: &Downloader) -> JreResult<Self> {
         debug!("Getting the JRE manifest from Mojang servers");
-        let response = reqwest::Client::new()
-            .get(constants::JRE_MANIFEST_URL)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<VersionsManifest>()
+        let response = downloader
+            .get_json::<VersionsManifest>(downloader.endpoints().jre_manifest())
             .await?;
 
         Ok(response)
@@ -68,18 +61,15 @@ impl VersionsManifest {
     pub async fn get_files(
         &self,
         version_type: &VersionType,
+        downloader: &Downloader,
     ) -> JreResult<HashMap<String, JreFile>> {
         debug!(message = "Getting the files", version_type = ?version_type);
         let url = self.get_runtime_url(version_type)?;
 
         debug!(message = "Getting the files from the URL", url = ?url);
-        let resp = reqwest::get(url).await?;
+        let files = downloader.get_json::<JreManifestFilesList>(&url).await?;
 
-        if !resp.status().is_success() {
-            return Err(JreError::ManifestReadError);
-        }
-
-        Ok(resp.json::<JreManifestFilesList>().await?.files)
+        Ok(files.files)
     }
 }
 
@@ -93,7 +83,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_manifest_alpha() {
-        let url = VersionsManifest::get().await;
+        let url = VersionsManifest::get(Downloader::shared()).await;
         assert!(url.is_ok());
     }
 }
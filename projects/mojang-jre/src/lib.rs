@@ -8,7 +8,6 @@
 )))]
 compile_error!("Unsupported platform");
 
-mod constants;
 mod download;
 mod errors;
 pub mod jre;
@@ -16,3 +15,5 @@ mod mojang_jre;
 
 pub use mojang_jre::*;
 pub mod callback;
+
+pub use bauxite_download::{Downloader, DownloaderBuilder, Endpoints};
@@ -3,12 +3,13 @@ use std::{
     sync::{atomic::AtomicUsize, Arc},
 };
 
+use bauxite_download::Downloader;
 use tokio::task::JoinSet;
 use tracing::debug;
 
 use crate::{
     callback::{DefaultDownloadCallback, DownloadCallback, DownloadStep},
-    download::download_pool::retry_download_item,
+    download::download_pool::download_item,
     errors::JreResult,
     jre::{VersionType, VersionsManifest},
 };
@@ -17,6 +18,7 @@ pub struct MojangJre {
     version_type: VersionType,
     path: PathBuf,
     callback: Arc<dyn DownloadCallback>,
+    downloader: Downloader,
 }
 
 impl MojangJre {
@@ -29,16 +31,25 @@ impl MojangJre {
             version_type,
             path: path.as_ref().to_path_buf(),
             callback: callback.unwrap_or(Arc::new(DefaultDownloadCallback {})),
+            downloader: Downloader::shared().clone(),
         }
     }
 
+    /// Set the downloader used for the files (defaults to [`Downloader::shared`]).
+    pub fn with_downloader(mut self, downloader: Downloader) -> Self {
+        self.downloader = downloader;
+        self
+    }
+
     pub async fn download(&mut self) -> JreResult<()> {
         self.callback.on_start();
         self.callback.on_step(DownloadStep::Manifest);
 
         debug!("Getting the manifest data");
-        let manifest = VersionsManifest::get().await?;
-        let jre_files = manifest.get_files(&self.version_type).await?;
+        let manifest = VersionsManifest::get(&self.downloader).await?;
+        let jre_files = manifest
+            .get_files(&self.version_type, &self.downloader)
+            .await?;
 
         // Checking the files
         self.callback.on_step(DownloadStep::Checking);
@@ -50,11 +61,12 @@ impl MojangJre {
         }
 
         let tasks = jre_files.iter().map(|(file_name, jre_file)| {
-            let jre_file_path = self.path.join(&file_name);
+            let jre_file_path = self.path.join(file_name);
 
-            retry_download_item(
+            download_item(
                 jre_file_path.clone(),
                 jre_file.clone(),
+                self.downloader.clone(),
                 self.callback.clone(),
             )
         });
@@ -79,7 +91,7 @@ impl MojangJre {
                     }
                     Err(e) => {
                         debug!("Error downloading file: {:?}", e);
-                        return Err(e.into());
+                        return Err(e);
                     }
                 },
                 Err(e) => {